@@ -0,0 +1,185 @@
+use crate::t;
+use crate::utils::config::{AllowedGuild, BanPolicy};
+use crate::Data;
+use log::{debug, warn};
+use poise::serenity_prelude::{self as serenity, CreateEmbed, CreateEmbedFooter};
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+
+/// Applies the guild's ban policy to whoever invited a banned member, and updates
+/// their reward roles.
+pub async fn handle_guild_ban_add(
+    ctx: &serenity::Context,
+    guild_id: serenity::GuildId,
+    banned_user: &serenity::User,
+    data: &Data,
+) {
+    let config = data.config.get();
+    let guild_config = match config
+        .guilds
+        .allowed
+        .iter()
+        .find(|g| g.id == guild_id.to_string())
+    {
+        Some(guild_config) => guild_config,
+        None => return,
+    };
+    let creator_id = match crate::utils::db::get_inviter(
+        &data.db,
+        &guild_config.id,
+        &banned_user.id.to_string(),
+    )
+    .await
+    {
+        Ok(Some(creator_id)) => creator_id,
+        Ok(None) => {
+            debug!(
+                "Banned member {} of guild {} has no invite record.",
+                banned_user.id, guild_id
+            );
+            return;
+        }
+        Err(e) => {
+            warn!("Failed to look up inviter of {}: {}", banned_user.id, e);
+            return;
+        }
+    };
+
+    // The invitee may be recorded as having left already
+    crate::utils::rewards::evaluate(&ctx.http, data, guild_config, &creator_id).await;
+
+    let policy = match &guild_config.ban_policy {
+        Some(policy) => policy,
+        None => return,
+    };
+    let locale = config.get_guild_locale(&guild_config.id);
+    let actions = apply_policy(ctx, data, guild_config, policy, locale, &creator_id).await;
+
+    if policy.notify {
+        let mut params = HashMap::new();
+        params.insert("user", format!("<@{}>", banned_user.id));
+        params.insert("inviter", format!("<@{}>", creator_id));
+        params.insert("id", creator_id.clone());
+
+        let mut description = t!(locale, "mod_log.invitee_banned.description", params.clone());
+        if !actions.is_empty() {
+            description.push_str(&format!(
+                "\n\n**{}**:\n{}",
+                t!(locale, "mod_log.invitee_banned.actions"),
+                actions
+                    .iter()
+                    .map(|action| format!("• {}", action))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ));
+        }
+
+        let embed = CreateEmbed::default()
+            .title(t!(locale, "mod_log.invitee_banned.title"))
+            .description(description)
+            .color(0xFF3333)
+            .footer(CreateEmbedFooter::new(t!(
+                locale,
+                "mod_log.invitee_banned.footer",
+                params
+            )));
+        crate::utils::mod_log::post(&ctx.http, guild_config, embed).await;
+    }
+}
+
+/// Applies each configured consequence, returning a line per outcome for the mod log.
+async fn apply_policy(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_config: &AllowedGuild,
+    policy: &BanPolicy,
+    locale: &str,
+    creator_id: &str,
+) -> Vec<String> {
+    let mut actions = Vec::new();
+    let bot_id = ctx.cache.current_user().id.to_string();
+
+    if let Some(penalty) = policy.quota_penalty {
+        let params = HashMap::from([("count", penalty.to_string())]);
+        match crate::utils::db::add_quota_penalty(
+            &data.db,
+            &guild_config.id,
+            creator_id,
+            penalty,
+            &bot_id,
+        )
+        .await
+        {
+            Ok(()) => actions.push(t!(locale, "mod_log.invitee_banned.quota_penalty", params)),
+            Err(e) => {
+                warn!("Failed to penalize inviter {}: {}", creator_id, e);
+                actions.push(t!(locale, "mod_log.invitee_banned.failed.quota_penalty"));
+            }
+        }
+    }
+
+    if let Some(days) = policy.suspend_days {
+        let params = HashMap::from([("days", days.to_string())]);
+        match crate::utils::db::suspend_invites(
+            &data.db,
+            &guild_config.id,
+            creator_id,
+            days,
+            &bot_id,
+        )
+        .await
+        {
+            Ok(()) => actions.push(t!(locale, "mod_log.invitee_banned.suspended", params)),
+            Err(e) => {
+                warn!("Failed to suspend inviter {}: {}", creator_id, e);
+                actions.push(t!(locale, "mod_log.invitee_banned.failed.suspended"));
+            }
+        }
+    }
+
+    if policy.strip_roles {
+        match strip_allowed_roles(ctx, guild_config, creator_id).await {
+            Ok(()) => actions.push(t!(locale, "mod_log.invitee_banned.roles_removed")),
+            Err(e) => {
+                warn!("Failed to remove roles of inviter {}: {}", creator_id, e);
+                actions.push(t!(locale, "mod_log.invitee_banned.failed.roles_removed"));
+            }
+        }
+    }
+
+    actions
+}
+
+async fn strip_allowed_roles(
+    ctx: &serenity::Context,
+    guild_config: &AllowedGuild,
+    creator_id: &str,
+) -> Result<(), serenity::Error> {
+    let (guild_id, user_id) = match (
+        guild_config.id.parse::<NonZeroU64>(),
+        creator_id.parse::<NonZeroU64>(),
+    ) {
+        (Ok(guild_id), Ok(user_id)) => (
+            serenity::GuildId::from(guild_id),
+            serenity::UserId::from(user_id),
+        ),
+        _ => return Ok(()),
+    };
+    let member = guild_id.member(&ctx.http, user_id).await?;
+
+    let roles: Vec<serenity::RoleId> = member
+        .roles
+        .iter()
+        .filter(|role_id| {
+            guild_config
+                .allowed_roles
+                .iter()
+                .any(|allowed| allowed.id == role_id.to_string())
+        })
+        .copied()
+        .collect();
+    if roles.is_empty() {
+        return Ok(());
+    }
+    member.remove_roles(&ctx.http, &roles).await
+}
@@ -1,6 +1,14 @@
+use crate::t;
+use crate::utils::alt_detection::{assess, Invitee};
+use crate::utils::config::{AllowedGuild, MismatchAction};
+use crate::utils::db::InviteDetails;
+use crate::utils::invite_tracker::{Attribution, TrackedInvite};
 use crate::Data;
-use log::debug;
-use poise::serenity_prelude::{self as serenity};
+use chrono::Utc;
+use log::{debug, info, warn};
+use poise::serenity_prelude::{self as serenity, CreateEmbed, CreateEmbedFooter};
+use std::collections::HashMap;
+use std::num::NonZeroU64;
 
 pub async fn handle_guild_member_add(
     ctx: &serenity::Context,
@@ -13,52 +21,387 @@ pub async fn handle_guild_member_add(
         new_member.user.id, guild_id
     );
     // Find if this guild is in the config
-    let guild_config = data
-        .config
+    let config = data.config.get();
+    let guild_config = match config
         .guilds
         .allowed
         .iter()
-        .find(|g| g.id == guild_id.to_string());
-    if guild_config.is_none() {
-        debug!("Guild {} not found in config, skipping...", guild_id);
-        return;
-    }
+        .find(|g| g.id == guild_id.to_string())
+    {
+        Some(guild_config) => guild_config,
+        None => {
+            debug!("Guild {} not found in config, skipping...", guild_id);
+            return;
+        }
+    };
 
     debug!("Guild {} found in config, checking invites...", guild_id);
-    let channel_id =
-        serenity::ChannelId::new(guild_config.unwrap().invite_channel.parse().unwrap());
-
-    // Get all invites from this server
-    if let Ok(invites) = channel_id.invites(&ctx.http).await {
-        for invite in invites
-            .into_iter()
-            .filter(|invite| invite.max_uses == 2 && invite.uses == 1)
-        {
+    let channel_id = serenity::ChannelId::new(guild_config.invite_channel.parse().unwrap());
+
+    // Must be checked before this join is recorded
+    let rejoin = crate::utils::db::has_joined_before(
+        &data.db,
+        &guild_id.to_string(),
+        &new_member.user.id.to_string(),
+    )
+    .await
+    .unwrap_or(false);
+
+    // Get all invites from this server and diff them against the last snapshot,
+    // one join at a time so concurrent joins don't race on the snapshot
+    let guard = data.invites.lock_guild(&guild_id.to_string()).await;
+    let invites = match channel_id.invites(&ctx.http).await {
+        Ok(invites) => invites,
+        Err(e) => {
+            warn!("Failed to fetch invites for guild {}: {}", guild_id, e);
+            return;
+        }
+    };
+    let attribution = data
+        .invites
+        .attribute(
+            &guild_id.to_string(),
+            invites
+                .iter()
+                .map(|invite| (invite.code.clone(), TrackedInvite::from_rich(invite)))
+                .collect(),
+            Utc::now().timestamp(),
+        )
+        .await;
+    drop(guard);
+
+    let _ = crate::utils::db::record_join_attribution(
+        &data.db,
+        &guild_id.to_string(),
+        &new_member.user.id.to_string(),
+        attribution.status(),
+        &attribution.codes().join(","),
+    )
+    .await;
+
+    let code = match attribution {
+        Attribution::Matched(code) => code,
+        Attribution::Ambiguous(codes) => {
+            warn!(
+                "Join of member {} in guild {} is ambiguous between invites {:?}.",
+                new_member.user.id, guild_id, codes
+            );
+            return;
+        }
+        Attribution::Unknown => {
             debug!(
-                "Invite {} found in database for guild {} member {}.",
-                invite.code, guild_id, new_member.user.id
+                "No consumed invite found for guild {} member {}.",
+                guild_id, new_member.user.id
             );
+            return;
+        }
+    };
 
-            // Check if this is our invite code
-            if let Ok(Some(invite_id)) =
-                crate::utils::db::find_invite_by_code(&data.db, &invite.code).await
-            {
-                debug!(
-                    "Invite {} found in database for guild {} member {}.",
-                    invite_id, guild_id, new_member.user.id
-                );
-                // Record the user
-                let _ = crate::utils::db::record_invite_use(
-                    &data.db,
-                    &invite_id,
-                    &new_member.user.id.to_string(),
+    // Check if this is our invite code
+    if let Ok(Some(invite_id)) = crate::utils::db::find_invite_by_code(&data.db, &code).await {
+        debug!(
+            "Invite {} found in database for guild {} member {}.",
+            invite_id, guild_id, new_member.user.id
+        );
+        // Record the user
+        let _ = crate::utils::db::record_invite_use(
+            &data.db,
+            &invite_id,
+            &new_member.user.id.to_string(),
+        )
+        .await;
+
+        if let Ok(Some(invite)) = crate::utils::db::get_invite(&data.db, &invite_id).await {
+            let held_back =
+                check_reservation(ctx, data, guild_config, &invite_id, &invite, new_member).await;
+            if !held_back {
+                grant_roles(ctx, data, guild_config, &invite_id, &invite, new_member).await;
+                if let Some(welcome) = &guild_config.welcome {
+                    crate::utils::welcome::greet(
+                        ctx,
+                        data,
+                        guild_config,
+                        welcome,
+                        &invite.creator_id,
+                        new_member,
+                    )
+                    .await;
+                }
+                crate::utils::rewards::evaluate(&ctx.http, data, guild_config, &invite.creator_id)
+                    .await;
+            }
+        }
+        check_invitee(
+            ctx,
+            data,
+            guild_config,
+            &invite_id,
+            &new_member.user,
+            rejoin,
+        )
+        .await;
+    }
+}
+
+/// Acts on a join through a link reserved for a different account, as the
+/// guild's `reservation_mismatch` says, and reports it to the mod log. Returns
+/// whether the member was kicked or quarantined.
+async fn check_reservation(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_config: &AllowedGuild,
+    invite_id: &str,
+    invite: &InviteDetails,
+    member: &serenity::Member,
+) -> bool {
+    let intended_for = match invite.intended_for.as_deref() {
+        Some(intended_for) => intended_for,
+        None => return false,
+    };
+    if crate::utils::reservation::matches(intended_for, member.user.id.get(), &member.user.name) {
+        return false;
+    }
+
+    debug!(
+        "Member {} of guild {} joined through invite {} reserved for {}.",
+        member.user.id, guild_config.id, invite_id, intended_for
+    );
+    let config = data.config.get();
+    let locale = config.get_guild_locale(&guild_config.id);
+
+    let action = match guild_config.reservation_mismatch.unwrap_or_default() {
+        MismatchAction::Flag => None,
+        MismatchAction::Kick => {
+            let result = member
+                .kick_with_reason(
+                    &ctx.http,
+                    &t!(locale, "mod_log.reservation_mismatch.kick_reason"),
                 )
                 .await;
+            Some(match result {
+                Ok(()) => ("mod_log.reservation_mismatch.kicked", true),
+                Err(e) => {
+                    warn!("Failed to kick member {}: {}", member.user.id, e);
+                    ("mod_log.reservation_mismatch.failed.kicked", false)
+                }
+            })
+        }
+        MismatchAction::Quarantine => {
+            let result = match guild_config
+                .quarantine_role
+                .as_deref()
+                .and_then(|id| id.parse::<NonZeroU64>().ok())
+            {
+                Some(role_id) => member
+                    .add_role(&ctx.http, serenity::RoleId::from(role_id))
+                    .await
+                    .map_err(|e| e.to_string()),
+                None => Err("no quarantine_role configured".to_string()),
+            };
+            Some(match result {
+                Ok(()) => ("mod_log.reservation_mismatch.quarantined", true),
+                Err(e) => {
+                    warn!("Failed to quarantine member {}: {}", member.user.id, e);
+                    ("mod_log.reservation_mismatch.failed.quarantined", false)
+                }
+            })
+        }
+    };
+
+    let mut params = HashMap::new();
+    params.insert("user", format!("<@{}>", member.user.id));
+    params.insert("inviter", format!("<@{}>", invite.creator_id));
+    params.insert(
+        "intended_for",
+        crate::utils::reservation::display(intended_for),
+    );
+    params.insert("id", member.user.id.to_string());
+
+    let mut description = t!(
+        locale,
+        "mod_log.reservation_mismatch.description",
+        params.clone()
+    );
+    if let Some((action, _)) = action {
+        description.push_str(&format!("\n\n{}", t!(locale, action)));
+    }
+
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "mod_log.reservation_mismatch.title"))
+        .description(description)
+        .color(0xFFA500)
+        .footer(CreateEmbedFooter::new(t!(
+            locale,
+            "mod_log.reservation_mismatch.footer",
+            params
+        )));
+    crate::utils::mod_log::post(&ctx.http, guild_config, embed).await;
 
-                // Delete the invite link
-                let _ = invite.delete(&ctx.http).await;
-                break;
+    action.is_some_and(|(_, held_back)| held_back)
+}
+
+/// Gives the member the roles their inviter's allowed roles hand out, and
+/// reports any that couldn't be given to the mod log.
+async fn grant_roles(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_config: &AllowedGuild,
+    invite_id: &str,
+    invite: &InviteDetails,
+    member: &serenity::Member,
+) {
+    let role_ids: Vec<serenity::RoleId> = invite
+        .grant_roles
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|id| id.trim().parse::<NonZeroU64>().ok())
+        .map(serenity::RoleId::from)
+        .collect();
+    if role_ids.is_empty() {
+        return;
+    }
+
+    let mut failed = Vec::new();
+    for role_id in role_ids {
+        match member.add_role(&ctx.http, role_id).await {
+            Ok(()) => info!(
+                "Gave member {} of guild {} role {} for joining through invite {} of {}.",
+                member.user.id, guild_config.id, role_id, invite_id, invite.creator_id
+            ),
+            Err(e) => {
+                warn!(
+                    "Failed to give member {} role {}: {}",
+                    member.user.id, role_id, e
+                );
+                failed.push(format!("<@&{}> (`{}`)", role_id, e));
             }
         }
     }
+    if failed.is_empty() {
+        return;
+    }
+
+    let config = data.config.get();
+    let locale = config.get_guild_locale(&guild_config.id);
+    let mut params = HashMap::new();
+    params.insert("user", format!("<@{}>", member.user.id));
+    params.insert("inviter", format!("<@{}>", invite.creator_id));
+    params.insert("roles", failed.join("\n"));
+    params.insert("id", member.user.id.to_string());
+
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "mod_log.grant_roles_failed.title"))
+        .description(t!(
+            locale,
+            "mod_log.grant_roles_failed.description",
+            params.clone()
+        ))
+        .color(0xFF3333)
+        .footer(CreateEmbedFooter::new(t!(
+            locale,
+            "mod_log.grant_roles_failed.footer",
+            params
+        )));
+    crate::utils::mod_log::post(&ctx.http, guild_config, embed).await;
+}
+
+/// Scores an attributed invitee, flagging the invite use and reporting it to the
+/// mod log if they look like an alt account.
+async fn check_invitee(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_config: &AllowedGuild,
+    invite_id: &str,
+    user: &serenity::User,
+    rejoin: bool,
+) {
+    let settings = match &guild_config.alt_detection {
+        Some(settings) => settings,
+        None => return,
+    };
+    let assessment = assess(
+        &Invitee {
+            account_age_days: (Utc::now().timestamp() - user.id.created_at().unix_timestamp())
+                / 86400,
+            default_avatar: user.avatar.is_none(),
+            rejoin,
+        },
+        settings,
+    );
+    if !assessment.flagged {
+        return;
+    }
+
+    debug!(
+        "Member {} of guild {} flagged: {}.",
+        user.id,
+        guild_config.id,
+        assessment.flags()
+    );
+    if let Err(e) = crate::utils::db::flag_invite_use(
+        &data.db,
+        invite_id,
+        &user.id.to_string(),
+        &assessment.flags(),
+    )
+    .await
+    {
+        warn!("Failed to flag invite use of member {}: {}", user.id, e);
+        return;
+    }
+
+    let creator_id = match crate::utils::db::get_invite(&data.db, invite_id).await {
+        Ok(Some(invite)) => invite.creator_id,
+        _ => return,
+    };
+    let flagged_count =
+        crate::utils::db::count_flagged_invitees(&data.db, &guild_config.id, &creator_id)
+            .await
+            .unwrap_or(0);
+
+    let config = data.config.get();
+    let locale = config.get_guild_locale(&guild_config.id);
+    let mut params = HashMap::new();
+    params.insert("user", format!("<@{}>", user.id));
+    params.insert("inviter", format!("<@{}>", creator_id));
+    params.insert("count", flagged_count.to_string());
+    params.insert("id", user.id.to_string());
+
+    let signals = assessment
+        .signals
+        .iter()
+        .map(|signal| {
+            format!(
+                "• {}",
+                t!(
+                    locale,
+                    format!("mod_log.flagged_join.signals.{}", signal.as_str()).as_str()
+                )
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut description = format!(
+        "{}\n\n{}",
+        t!(locale, "mod_log.flagged_join.description", params.clone()),
+        signals
+    );
+    if flagged_count >= settings.repeat_threshold.unwrap_or(3) as i64 {
+        description.push_str(&format!(
+            "\n\n{}",
+            t!(locale, "mod_log.flagged_join.repeat", params.clone())
+        ));
+    }
+
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "mod_log.flagged_join.title"))
+        .description(description)
+        .color(0xFFA500)
+        .footer(CreateEmbedFooter::new(t!(
+            locale,
+            "mod_log.flagged_join.footer",
+            params
+        )));
+    crate::utils::mod_log::post(&ctx.http, guild_config, embed).await;
 }
@@ -0,0 +1,48 @@
+use crate::Data;
+use log::{debug, warn};
+use poise::serenity_prelude::{self as serenity};
+
+/// Records that a member left, so retention-based counts can exclude their invite,
+/// and updates their inviter's reward roles.
+pub async fn handle_guild_member_remove(
+    ctx: &serenity::Context,
+    guild_id: serenity::GuildId,
+    user: &serenity::User,
+    data: &Data,
+) {
+    match crate::utils::db::record_member_left(
+        &data.db,
+        &guild_id.to_string(),
+        &user.id.to_string(),
+    )
+    .await
+    {
+        Ok(true) => debug!("Member {} left guild {}.", user.id, guild_id),
+        Ok(false) => return,
+        Err(e) => {
+            warn!(
+                "Failed to record member {} leaving guild {}: {}",
+                user.id, guild_id, e
+            );
+            return;
+        }
+    }
+
+    let config = data.config.get();
+    let Some(guild_config) = config
+        .guilds
+        .allowed
+        .iter()
+        .find(|g| g.id == guild_id.to_string())
+    else {
+        return;
+    };
+    if let Ok(Some(info)) =
+        crate::utils::db::get_user_invite_info(&data.db, &guild_config.id, &user.id.to_string())
+            .await
+    {
+        if let Some(creator_id) = info.creator_id {
+            crate::utils::rewards::evaluate(&ctx.http, data, guild_config, &creator_id).await;
+        }
+    }
+}
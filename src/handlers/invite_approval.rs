@@ -0,0 +1,190 @@
+use crate::t;
+use crate::utils::db::ReviewedInvite;
+use crate::Data;
+use log::warn;
+use poise::serenity_prelude::{
+    self as serenity, ComponentInteraction, CreateEmbed, CreateEmbedFooter,
+    CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, UserId,
+};
+use std::collections::HashMap;
+
+/// Handles the Approve and Deny buttons of an approval request card.
+pub async fn handle_approval_button(
+    ctx: &serenity::Context,
+    interaction: &ComponentInteraction,
+    data: &Data,
+) {
+    let Some((approve, invite_id)) =
+        crate::utils::approval::parse_custom_id(&interaction.data.custom_id)
+    else {
+        return;
+    };
+    let Some(guild_id) = interaction.guild_id else {
+        return;
+    };
+    let guild_id = guild_id.to_string();
+    let config = data.config.get();
+    let locale = config.get_guild_locale(&guild_id);
+
+    let is_admin = match (
+        interaction.member.as_ref(),
+        config.guilds.allowed.iter().find(|g| g.id == guild_id),
+    ) {
+        (Some(member), Some(guild_config)) => {
+            crate::utils::permissions::is_admin(member, guild_config)
+        }
+        _ => false,
+    };
+    if !is_admin {
+        reply_error(ctx, interaction, locale, "approval.errors.not_admin").await;
+        return;
+    }
+
+    let reviewer_id = interaction.user.id.to_string();
+    let reviewed =
+        match crate::utils::db::review_invite(&data.db, invite_id, approve, &reviewer_id).await {
+            Ok(Some(reviewed)) => reviewed,
+            Ok(None) => {
+                reply_error(ctx, interaction, locale, "approval.errors.already_reviewed").await;
+                return;
+            }
+            Err(e) => {
+                warn!("Failed to review invite {}: {}", invite_id, e);
+                reply_error(ctx, interaction, locale, "approval.errors.failed").await;
+                return;
+            }
+        };
+
+    // Record the decision on the card and take the buttons away
+    let (decision_key, color) = if approve {
+        ("approval.decision.approved", 0x4CACEE)
+    } else {
+        ("approval.decision.denied", 0xFF3333)
+    };
+    let mut embed = interaction
+        .message
+        .embeds
+        .first()
+        .cloned()
+        .map(CreateEmbed::from)
+        .unwrap_or_default();
+    embed = embed
+        .field(
+            t!(locale, "approval.decision.title"),
+            t!(
+                locale,
+                decision_key,
+                HashMap::from([("moderator", format!("<@{}>", reviewer_id))])
+            ),
+            false,
+        )
+        .color(color);
+    if let Err(e) = interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .components(vec![]),
+            ),
+        )
+        .await
+    {
+        warn!(
+            "Failed to update approval card of invite {}: {}",
+            invite_id, e
+        );
+    }
+
+    notify_requester(ctx, data, locale, invite_id, approve, &reviewed).await;
+}
+
+/// DMs the requester the outcome, with the link if it was approved.
+async fn notify_requester(
+    ctx: &serenity::Context,
+    data: &Data,
+    locale: &str,
+    invite_id: &str,
+    approve: bool,
+    reviewed: &ReviewedInvite,
+) {
+    let Ok(creator_id) = reviewed.creator_id.parse::<u64>() else {
+        return;
+    };
+    let guild_name = reviewed
+        .guild_id
+        .parse::<u64>()
+        .ok()
+        .and_then(|id| ctx.cache.guild(id).map(|guild| guild.name.clone()))
+        .unwrap_or_default();
+
+    let mut params = HashMap::new();
+    params.insert("guild", guild_name);
+    params.insert(
+        "url",
+        format!(
+            "{}/invite/{}",
+            data.config.get().server.external_url,
+            invite_id
+        ),
+    );
+    params.insert(
+        "note",
+        reviewed
+            .approval_note
+            .clone()
+            .unwrap_or_else(|| t!(locale, "approval.request.no_note")),
+    );
+
+    let key = if approve {
+        "approval.dm.approved"
+    } else {
+        "approval.dm.denied"
+    };
+    let embed = CreateEmbed::default()
+        .title(t!(locale, format!("{}.title", key).as_str()))
+        .description(t!(
+            locale,
+            format!("{}.description", key).as_str(),
+            params.clone()
+        ))
+        .color(if approve { 0x4CACEE } else { 0xFF3333 })
+        .footer(CreateEmbedFooter::new(t!(
+            locale,
+            format!("{}.footer", key).as_str(),
+            params
+        )));
+
+    if let Err(e) = UserId::new(creator_id)
+        .direct_message(ctx, CreateMessage::new().embed(embed))
+        .await
+    {
+        warn!(
+            "Failed to DM {} about invite {}: {}",
+            creator_id, invite_id, e
+        );
+    }
+}
+
+async fn reply_error(
+    ctx: &serenity::Context,
+    interaction: &ComponentInteraction,
+    locale: &str,
+    error_key: &str,
+) {
+    let embed = CreateEmbed::default()
+        .title(t!(locale, format!("{}.title", error_key).as_str()))
+        .description(t!(locale, format!("{}.description", error_key).as_str()))
+        .color(0xFF3333);
+
+    let _ = interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .ephemeral(true),
+            ),
+        )
+        .await;
+}
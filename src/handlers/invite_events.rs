@@ -0,0 +1,64 @@
+use crate::utils::invite_tracker::TrackedInvite;
+use crate::Data;
+use chrono::Utc;
+use log::{debug, warn};
+use poise::serenity_prelude::{self as serenity};
+
+/// Takes an initial snapshot of every configured guild's invite channel.
+pub async fn seed_invite_cache(ctx: &serenity::Context, data: &Data) {
+    let config = data.config.get();
+    for guild_config in &config.guilds.allowed {
+        let channel_id = match guild_config.invite_channel.parse() {
+            Ok(id) => serenity::ChannelId::new(id),
+            Err(_) => continue,
+        };
+
+        match channel_id.invites(&ctx.http).await {
+            Ok(invites) => {
+                debug!(
+                    "Seeded {} invites for guild {}.",
+                    invites.len(),
+                    guild_config.id
+                );
+                data.invites
+                    .seed(
+                        &guild_config.id,
+                        invites
+                            .into_iter()
+                            .map(|invite| (invite.code.clone(), TrackedInvite::from_rich(&invite)))
+                            .collect(),
+                    )
+                    .await;
+            }
+            Err(e) => warn!(
+                "Failed to fetch invites for guild {}: {}",
+                guild_config.id, e
+            ),
+        }
+    }
+}
+
+pub async fn handle_invite_create(event: &serenity::InviteCreateEvent, data: &Data) {
+    if let Some(guild_id) = event.guild_id {
+        data.invites
+            .insert(
+                &guild_id.to_string(),
+                &event.code,
+                TrackedInvite::new(
+                    event.uses,
+                    event.max_uses as u64,
+                    event.created_at.unix_timestamp(),
+                    event.max_age,
+                ),
+            )
+            .await;
+    }
+}
+
+pub async fn handle_invite_delete(event: &serenity::InviteDeleteEvent, data: &Data) {
+    if let Some(guild_id) = event.guild_id {
+        data.invites
+            .mark_deleted(&guild_id.to_string(), &event.code, Utc::now().timestamp())
+            .await;
+    }
+}
@@ -1 +1,5 @@
+pub mod guild_ban_add;
 pub mod guild_member_add;
+pub mod guild_member_remove;
+pub mod invite_approval;
+pub mod invite_events;
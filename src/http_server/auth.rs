@@ -0,0 +1,141 @@
+use super::server::AppState;
+use crate::t;
+use axum::{
+    extract::{Request, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub message: String,
+}
+
+/// Rejects requests that don't carry `Authorization: Bearer <server.admin_token>`.
+pub async fn require_admin(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let config = state.config.get();
+    let locale = config.i18n.default_locale.as_str();
+
+    let expected = match config.server.admin_token.as_deref() {
+        Some(token) if !token.is_empty() => token,
+        _ => {
+            return error_response(
+                StatusCode::FORBIDDEN,
+                t!(locale, "http.errors.admin_disabled"),
+            );
+        }
+    };
+
+    let provided = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => {
+            next.run(request).await
+        }
+        _ => error_response(
+            StatusCode::UNAUTHORIZED,
+            t!(locale, "http.errors.unauthorized"),
+        ),
+    }
+}
+
+pub fn error_response(status: StatusCode, message: String) -> Response {
+    (status, Json(ErrorResponse { message })).into_response()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_server::server::build_router;
+    use crate::utils::config::SharedConfig;
+    use crate::utils::test_helpers::TestContext;
+    use axum::body::{to_bytes, Body};
+    use tower::ServiceExt;
+
+    async fn test_state() -> Arc<AppState> {
+        let test_ctx = TestContext::new().await;
+        Arc::new(AppState {
+            db: test_ctx.db,
+            config: SharedConfig::new(test_ctx.config),
+            invites: Default::default(),
+        })
+    }
+
+    fn config_request(token: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri("/config");
+        if let Some(token) = token {
+            builder = builder.header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_config_requires_token() {
+        let app = build_router(test_state().await);
+
+        let response = app.clone().oneshot(config_request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app.oneshot(config_request(Some("wrong"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_config_redacts_secrets() {
+        let app = build_router(test_state().await);
+
+        let response = app
+            .oneshot(config_request(Some("test_admin_token")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(!body.contains("test_token"));
+        assert!(!body.contains("test_admin_token"));
+    }
+
+    #[tokio::test]
+    async fn test_admin_disabled_without_token() {
+        let test_ctx = TestContext::new().await;
+        let mut config = test_ctx.config;
+        config.server.admin_token = None;
+        let app = build_router(Arc::new(AppState {
+            db: test_ctx.db,
+            config: SharedConfig::new(config),
+            invites: Default::default(),
+        }));
+
+        let response = app
+            .oneshot(config_request(Some("test_admin_token")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+    }
+}
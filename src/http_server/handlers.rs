@@ -1,11 +1,16 @@
+use super::auth::error_response;
 use super::server::AppState;
 use crate::t;
 use crate::utils::config::Config;
+use crate::utils::db::{InviteFilter, InviteListEntry, InviteStatus};
+use crate::utils::export::ExportFormat;
+use crate::utils::invite_tracker::TrackedInvite;
+use crate::utils::revoke::{RevokeError, Revoker};
 use axum::{
     body::Body,
-    extract::{Path, State},
-    http::StatusCode,
-    response::{IntoResponse, Json, Redirect},
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Json, Redirect, Response},
 };
 use poise::serenity_prelude::CreateInvite;
 use serde::{Deserialize, Serialize};
@@ -19,100 +24,137 @@ pub struct ConfigResponse {
     config: Config,
 }
 
-#[derive(Serialize, Deserialize)]
-struct ErrorResponse {
-    message: String,
-}
-
 #[derive(Serialize, Deserialize)]
 pub struct LocalesResponse {
     pub locales: Vec<String>,
 }
 
+#[derive(Deserialize)]
+pub struct ListInvitesQuery {
+    guild_id: Option<String>,
+    creator_id: Option<String>,
+    status: Option<InviteStatus>,
+    /// First creation day, `YYYY-MM-DD`.
+    from: Option<chrono::NaiveDate>,
+    /// Last creation day, `YYYY-MM-DD`.
+    to: Option<chrono::NaiveDate>,
+    /// `next_cursor` of the previous page.
+    cursor: Option<String>,
+    limit: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct ListInvitesResponse {
+    invites: Vec<InviteListEntry>,
+    next_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    format: ExportFormat,
+    /// First day, `YYYY-MM-DD`.
+    from: Option<chrono::NaiveDate>,
+    /// Last day, `YYYY-MM-DD`.
+    to: Option<chrono::NaiveDate>,
+}
+
+const DEFAULT_LIST_LIMIT: u32 = 50;
+const MAX_LIST_LIMIT: u32 = 200;
+
 pub async fn handle_invite(
     Path(invite_id): Path<String>,
     State(state): State<Arc<AppState>>,
-) -> Result<Redirect, (StatusCode, String)> {
-    let invite_record = crate::utils::db::get_unused_invite(&state.db, &invite_id)
+) -> Result<Redirect, (StatusCode, Html<String>)> {
+    let config = state.config.get();
+    let locale = config.i18n.default_locale.as_str();
+
+    let invite_record = match crate::utils::db::get_unused_invite(&state.db, &invite_id)
         .await
         .map_err(|e| {
-            (
+            error_page(
                 StatusCode::INTERNAL_SERVER_ERROR,
+                locale,
                 t!(
-                    &state.config.i18n.default_locale,
+                    locale,
                     "http.errors.internal",
                     HashMap::from([("error", e.to_string())])
                 ),
             )
-        })?
-        .ok_or((
-            StatusCode::BAD_REQUEST,
-            t!(
-                &state.config.i18n.default_locale,
-                "http.errors.invalid_invite"
-            ),
-        ))?;
+        })? {
+        Some(record) => record,
+        None => return Err(invalid_invite_page(&state, &config, &invite_id).await),
+    };
 
     if let Some(code) = invite_record.code {
         return Ok(Redirect::temporary(&format!("https://discord.gg/{}", code)));
     }
 
-    let guild_config = state
-        .config
+    let guild_config = config
         .guilds
         .allowed
         .iter()
         .find(|g| g.id == invite_record.guild_id)
-        .ok_or((
-            StatusCode::BAD_REQUEST,
-            t!(
-                &state.config.i18n.default_locale,
-                "http.errors.server_not_found"
-            ),
-        ))?;
+        .ok_or_else(|| {
+            error_page(
+                StatusCode::BAD_REQUEST,
+                locale,
+                t!(locale, "http.errors.server_not_found"),
+            )
+        })?;
 
     let channel_id = ChannelId::new(guild_config.invite_channel.parse().map_err(|_| {
-        (
+        error_page(
             StatusCode::BAD_REQUEST,
-            t!(
-                &state.config.i18n.default_locale,
-                "http.errors.invalid_channel"
-            ),
+            locale,
+            t!(locale, "http.errors.invalid_channel"),
         )
     })?);
 
     let max_age = guild_config
         .max_age
-        .unwrap_or(state.config.bot.default_invite_max_age);
+        .unwrap_or(config.bot.default_invite_max_age);
 
-    let http = Http::new(&state.config.bot.token);
+    let http = Http::new(&config.bot.token);
     let invite = channel_id
         .create_invite(
             &http,
             CreateInvite::default()
                 .max_age(max_age)
-                .max_uses(2)
+                .max_uses(invite_record.max_uses)
                 .temporary(false),
         )
         .await
         .map_err(|e| {
-            (
+            error_page(
                 StatusCode::INTERNAL_SERVER_ERROR,
+                locale,
                 t!(
-                    &state.config.i18n.default_locale,
+                    locale,
                     "http.errors.create_failed",
                     HashMap::from([("error", e.to_string())])
                 ),
             )
         })?;
 
+    // Known before Discord's invite create event, so a join right after is attributed
+    state
+        .invites
+        .insert(
+            &invite_record.guild_id,
+            &invite.code,
+            TrackedInvite::from_rich(&invite),
+        )
+        .await;
+
     crate::utils::db::update_invite_code(&state.db, &invite_id, &invite.code)
         .await
         .map_err(|e| {
-            (
+            error_page(
                 StatusCode::INTERNAL_SERVER_ERROR,
+                locale,
                 t!(
-                    &state.config.i18n.default_locale,
+                    locale,
                     "http.errors.update_failed",
                     HashMap::from([("error", e.to_string())])
                 ),
@@ -125,32 +167,271 @@ pub async fn handle_invite(
     )))
 }
 
+/// Explains why a link can't be redeemed, in the guild's locale when the link is known.
+async fn invalid_invite_page(
+    state: &AppState,
+    config: &Config,
+    invite_id: &str,
+) -> (StatusCode, Html<String>) {
+    let invite = crate::utils::db::get_invite(&state.db, invite_id)
+        .await
+        .ok()
+        .flatten();
+
+    match invite {
+        Some(invite) if invite.is_awaiting_approval() => {
+            let locale = config.get_guild_locale(&invite.guild_id);
+            error_page(
+                StatusCode::FORBIDDEN,
+                locale,
+                t!(locale, "http.errors.pending_approval"),
+            )
+        }
+        Some(invite) if invite.is_expired() => {
+            let locale = config.get_guild_locale(&invite.guild_id);
+            error_page(
+                StatusCode::GONE,
+                locale,
+                t!(locale, "http.errors.expired_invite"),
+            )
+        }
+        _ => {
+            let locale = config.i18n.default_locale.as_str();
+            error_page(
+                StatusCode::BAD_REQUEST,
+                locale,
+                t!(locale, "http.errors.invalid_invite"),
+            )
+        }
+    }
+}
+
+fn error_page(status: StatusCode, locale: &str, message: String) -> (StatusCode, Html<String>) {
+    let message = message
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    (
+        status,
+        Html(format!(
+            "<!DOCTYPE html>\n<html lang=\"{locale}\">\n<head>\n<meta charset=\"UTF-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">\n\
+             <title>{title}</title>\n</head>\n\
+             <body style=\"font-family: sans-serif; text-align: center; padding: 4em 1em;\">\n\
+             <h1>{title}</h1>\n<p>{message}</p>\n</body>\n</html>\n",
+            title = t!(locale, "http.page_title"),
+        )),
+    )
+}
+
 pub async fn get_config(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let config = &state.config;
     (
         StatusCode::OK,
         Json(ConfigResponse {
-            config: config.as_ref().clone(),
+            config: state.config.get().redacted(),
         }),
     )
 }
 
 pub async fn update_config(
-    State(_state): State<Arc<AppState>>,
-    Json(payload): Json<ConfigResponse>,
-) -> impl IntoResponse {
-    println!("Received config: {:?}", payload.config);
-    payload
+    State(state): State<Arc<AppState>>,
+    Json(mut payload): Json<ConfigResponse>,
+) -> Response {
+    let current = state.config.get();
+    let locale = current.i18n.default_locale.as_str();
+    payload.config.restore_secrets(&current);
+
+    if let Err(e) = payload.config.validate() {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            t!(
+                locale,
+                "http.errors.invalid_config",
+                HashMap::from([("error", e.to_string())])
+            ),
+        );
+    }
+
+    if let Err(e) = payload
         .config
         .save(std::env::var("CONFIG_PATH").unwrap().as_str())
-        .unwrap();
+    {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            t!(
+                locale,
+                "http.errors.save_failed",
+                HashMap::from([("error", e.to_string())])
+            ),
+        );
+    }
+
+    state.config.replace(payload.config.clone());
+
+    (
+        StatusCode::OK,
+        Json(ConfigResponse {
+            config: payload.config.redacted(),
+        }),
+    )
+        .into_response()
+}
+
+pub async fn reload_config(State(state): State<Arc<AppState>>) -> Response {
+    if let Err(e) = state
+        .config
+        .reload(std::env::var("CONFIG_PATH").unwrap().as_str())
+    {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            t!(
+                &state.config.get().i18n.default_locale,
+                "http.errors.reload_failed",
+                HashMap::from([("error", e.to_string())])
+            ),
+        );
+    }
 
     (
         StatusCode::OK,
         Json(ConfigResponse {
-            config: payload.config.clone(),
+            config: state.config.get().redacted(),
+        }),
+    )
+        .into_response()
+}
+
+pub async fn revoke_invite(
+    Path(invite_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let config = state.config.get();
+    let locale = config.i18n.default_locale.as_str();
+    let http = Http::new(&config.bot.token);
+
+    let result = crate::utils::revoke::revoke_invite(
+        &http,
+        &state.db,
+        &state.invites,
+        &invite_id,
+        Revoker {
+            user_id: "api",
+            is_admin: true,
+            guild_id: None,
+        },
+    )
+    .await;
+
+    let (status, key) = match result {
+        Ok(()) => return StatusCode::NO_CONTENT.into_response(),
+        Err(RevokeError::NotFound) => (StatusCode::NOT_FOUND, "http.errors.invite_not_found"),
+        Err(RevokeError::AlreadyUsed) => (StatusCode::CONFLICT, "http.errors.invite_used"),
+        Err(RevokeError::AlreadyRevoked) => (StatusCode::CONFLICT, "http.errors.invite_revoked"),
+        Err(RevokeError::Forbidden) => (StatusCode::FORBIDDEN, "http.errors.unauthorized"),
+        Err(RevokeError::Database(e)) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                t!(
+                    locale,
+                    "http.errors.internal",
+                    HashMap::from([("error", e.to_string())])
+                ),
+            );
+        }
+    };
+
+    error_response(status, t!(locale, key))
+}
+
+pub async fn list_invites(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ListInvitesQuery>,
+) -> Response {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT) as usize;
+    let filter = InviteFilter {
+        guild_id: query.guild_id.as_deref(),
+        creator_id: query.creator_id.as_deref(),
+        status: query.status,
+        created_from: query.from,
+        created_to: query.to,
+        after: query.cursor.as_deref(),
+    };
+
+    // Fetch one extra row to tell whether another page follows
+    let mut invites =
+        match crate::utils::db::list_invites(&state.db, &filter, limit as i64 + 1).await {
+            Ok(invites) => invites,
+            Err(e) => {
+                return error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    t!(
+                        &state.config.get().i18n.default_locale,
+                        "http.errors.internal",
+                        HashMap::from([("error", e.to_string())])
+                    ),
+                );
+            }
+        };
+
+    let next_cursor = if invites.len() > limit {
+        invites.truncate(limit);
+        invites.last().map(|invite| invite.id.clone())
+    } else {
+        None
+    };
+
+    (
+        StatusCode::OK,
+        Json(ListInvitesResponse {
+            invites,
+            next_cursor,
         }),
     )
+        .into_response()
+}
+
+pub async fn export_invites(
+    Path(guild_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ExportQuery>,
+) -> Response {
+    let locale = state.config.get().i18n.default_locale.clone();
+    let body = crate::utils::db::get_invite_history(&state.db, &guild_id, query.from, query.to)
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|history| query.format.render(&history).map_err(|e| e.to_string()));
+
+    match body {
+        Ok(body) => (
+            StatusCode::OK,
+            [
+                (
+                    header::CONTENT_TYPE,
+                    query.format.content_type().to_string(),
+                ),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"{}\"",
+                        crate::utils::export::file_name(&guild_id, query.format)
+                    ),
+                ),
+            ],
+            body,
+        )
+            .into_response(),
+        Err(error) => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            t!(
+                &locale,
+                "http.errors.internal",
+                HashMap::from([("error", error)])
+            ),
+        ),
+    }
 }
 
 pub async fn get_locales(State(_state): State<Arc<AppState>>) -> impl IntoResponse {
@@ -186,3 +467,116 @@ pub async fn serve_embedded_files(path: Option<Path<String>>) -> impl IntoRespon
         None => (StatusCode::NOT_FOUND, "Not Found".into_response()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_server::server::build_router;
+    use crate::utils::config::SharedConfig;
+    use crate::utils::db::NewInvite;
+    use crate::utils::test_helpers::TestContext;
+    use axum::body::to_bytes;
+    use axum::http::{header::AUTHORIZATION, Request};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_list_invites_pages() {
+        let test_ctx = TestContext::new().await;
+        for id in ["a", "b", "c"] {
+            crate::utils::db::create_invite(
+                &test_ctx.db,
+                &NewInvite {
+                    id,
+                    guild_id: "123",
+                    creator_id: "456",
+                    max_uses: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        }
+        let app = build_router(Arc::new(AppState {
+            db: test_ctx.db,
+            config: SharedConfig::new(test_ctx.config),
+            invites: Default::default(),
+        }));
+
+        let get = |uri: &str| {
+            Request::builder()
+                .uri(uri)
+                .header(AUTHORIZATION, "Bearer test_admin_token")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(get("/api/invites?limit=2&status=pending"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains(r#""id":"c""#));
+        assert!(body.contains(r#""id":"b""#));
+        assert!(body.contains(r#""next_cursor":"b""#));
+
+        let response = app
+            .clone()
+            .oneshot(get("/api/invites?limit=2&cursor=b"))
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains(r#""id":"a""#));
+        assert!(body.contains(r#""next_cursor":null"#));
+
+        let response = app
+            .oneshot(get("/api/invites?from=yesterday"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_export_invites() {
+        let test_ctx = TestContext::new().await;
+        crate::utils::db::create_invite(
+            &test_ctx.db,
+            &NewInvite {
+                id: "a",
+                guild_id: "123",
+                creator_id: "456",
+                max_uses: 1,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let app = build_router(Arc::new(AppState {
+            db: test_ctx.db,
+            config: SharedConfig::new(test_ctx.config),
+            invites: Default::default(),
+        }));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/guilds/123/invites/export?format=csv")
+                    .header(AUTHORIZATION, "Bearer test_admin_token")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/csv; charset=utf-8"
+        );
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.lines().nth(1).unwrap().starts_with("a,456,,"));
+    }
+}
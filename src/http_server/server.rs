@@ -1,7 +1,15 @@
+use super::auth::require_admin;
 use super::handlers::{get_config, get_locales, handle_invite, serve_embedded_files};
-use crate::http_server::handlers::update_config;
-use crate::utils::config::Config;
-use axum::{routing::get, Router};
+use crate::http_server::handlers::{
+    export_invites, list_invites, reload_config, revoke_invite, update_config,
+};
+use crate::utils::config::SharedConfig;
+use crate::utils::invite_tracker::InviteTracker;
+use axum::{
+    middleware,
+    routing::{delete, get, post},
+    Router,
+};
 use sqlx::SqlitePool;
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -10,24 +18,41 @@ use tower_http::cors::CorsLayer;
 #[derive(Clone)]
 pub struct AppState {
     pub db: SqlitePool,
-    pub config: Arc<Config>,
+    pub config: SharedConfig,
+    pub invites: Arc<InviteTracker>,
 }
 
-pub async fn run_server(config: Config, db: SqlitePool) {
-    let bind_addr = config.server.bind.clone();
-    let app_state = Arc::new(AppState {
-        db,
-        config: Arc::new(config),
-    });
+pub fn build_router(app_state: Arc<AppState>) -> Router {
+    let admin = Router::new()
+        .route("/config", get(get_config).post(update_config))
+        .route("/admin/reload-config", post(reload_config))
+        .route("/api/invites", get(list_invites))
+        .route("/api/invites/{id}", delete(revoke_invite))
+        .route("/api/guilds/{id}/invites/export", get(export_invites))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            require_admin,
+        ));
 
-    let app = Router::new()
+    Router::new()
         .route("/invite/{id}", get(handle_invite))
-        .route("/config", get(get_config).post(update_config))
         .route("/locales", get(get_locales))
+        .merge(admin)
         .route("/", get(serve_embedded_files))
         .route("/{*path}", get(serve_embedded_files))
         .layer(CorsLayer::permissive())
-        .with_state(app_state.clone());
+        .with_state(app_state)
+}
+
+pub async fn run_server(config: SharedConfig, db: SqlitePool, invites: Arc<InviteTracker>) {
+    let bind_addr = config.get().server.bind.clone();
+    let app_state = Arc::new(AppState {
+        db,
+        config,
+        invites,
+    });
+
+    let app = build_router(app_state);
 
     let addr: SocketAddr = bind_addr
         .parse()
@@ -2,6 +2,7 @@ use poise::serenity_prelude::CreateEmbedFooter;
 use poise::serenity_prelude::{self as serenity, async_trait};
 use sqlx::sqlite::SqlitePool;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 mod handlers;
 mod http_server;
@@ -9,18 +10,22 @@ mod i18n;
 mod migrations;
 mod public;
 mod slash_commands;
+mod tasks;
 mod utils;
 
 use crate::http_server::server::run_server;
-use utils::config::Config;
+use std::time::Duration;
+use utils::config::{Config, SharedConfig};
+use utils::invite_tracker::InviteTracker;
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Context<'a> = poise::Context<'a, Data, Error>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Data {
     db: SqlitePool,
-    config: utils::config::Config,
+    config: SharedConfig,
+    invites: Arc<InviteTracker>,
 }
 
 async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
@@ -39,7 +44,8 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
         poise::FrameworkError::Command { error, ctx, .. } => {
             println!("Command error: {:?}", error);
             let guild_id = ctx.guild_id().unwrap();
-            let locale = ctx.data().config.get_guild_locale(&guild_id.to_string());
+            let config = ctx.data().config.get();
+            let locale = config.get_guild_locale(&guild_id.to_string());
 
             let embed = poise::serenity_prelude::CreateEmbed::default()
                 .title(t!(locale, "errors.command.title"))
@@ -70,36 +76,57 @@ async fn main() -> Result<(), Error> {
 
     env_logger::init();
 
-    let config = Config::load(std::env::var("CONFIG_PATH").unwrap().as_str())?;
+    let config_path = std::env::var("CONFIG_PATH").unwrap();
+    let config = Config::load(config_path.as_str())?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("migrate") {
+        return migrate(&config, args.iter().any(|a| a == "--dry-run")).await;
+    }
+    if args.first().map(String::as_str) == Some("import") {
+        let Some(path) = args.iter().skip(1).find(|a| !a.starts_with("--")) else {
+            return Err("usage: InvitationBot import <file.csv|file.json> [--dry-run]".into());
+        };
+        return import(&config, path, args.iter().any(|a| a == "--dry-run")).await;
+    }
 
     // Initialize database connection pool
     let db = utils::db::create_pool(&config.database.uri).await?;
 
+    let data = Data {
+        db,
+        config: SharedConfig::new(config.clone()),
+        invites: Arc::new(InviteTracker::default()),
+    };
+    data.config.watch(config_path, Duration::from_secs(5));
+
     // Start HTTP server
-    let server_config = config.clone();
-    let server_db = db.clone();
+    let server_data = data.clone();
     tokio::spawn(async move {
-        run_server(server_config, server_db).await;
+        run_server(server_data.config, server_data.db, server_data.invites).await;
     });
 
-    let db_clone = db.clone();
-    let config_clone1 = config.clone();
-    let config_clone2 = config.clone();
+    let framework_data = data.clone();
+    let task_data = data.clone();
 
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
             commands: vec![
                 slash_commands::ping::ping(),
                 slash_commands::invites::invites(),
+                slash_commands::invites_admin::invites_admin(),
                 slash_commands::inviter::inviter(),
+                slash_commands::invite_tree::invite_tree(),
                 slash_commands::invites_leaderboard::invites_leaderboard(),
+                slash_commands::rewards::rewards(),
             ],
             on_error: |error| Box::pin(on_error(error)),
             ..Default::default()
         })
         .setup(|ctx, _ready, framework| {
             Box::pin(async move {
-                let locale = config_clone1.i18n.default_locale.as_str();
+                let config = framework_data.config.get();
+                let locale = config.i18n.default_locale.as_str();
                 println!(
                     "{}",
                     t!(
@@ -109,10 +136,7 @@ async fn main() -> Result<(), Error> {
                     )
                 );
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
-                Ok(Data {
-                    db,
-                    config: config_clone1,
-                })
+                Ok(framework_data)
             })
         })
         .build();
@@ -120,29 +144,107 @@ async fn main() -> Result<(), Error> {
     let intents = serenity::GatewayIntents::non_privileged()
         | serenity::GatewayIntents::MESSAGE_CONTENT
         | serenity::GatewayIntents::GUILD_MEMBERS
+        | serenity::GatewayIntents::GUILD_MODERATION
         | serenity::GatewayIntents::GUILD_INVITES;
 
     let mut client = serenity::ClientBuilder::new(&config.bot.token, intents)
         .framework(framework)
-        .event_handler(Handler {
-            data: Data {
-                db: db_clone,
-                config: config_clone2,
-            },
-        })
+        .event_handler(Handler { data })
         .await?;
 
+    tasks::expired_invites::spawn(client.http.clone(), task_data.clone());
+    tasks::leaderboard_announcements::spawn(client.http.clone(), task_data.clone());
+    tasks::reward_sweep::spawn(client.http.clone(), task_data);
+
     client.start().await?;
 
     Ok(())
 }
 
+/// `InvitationBot migrate [--dry-run]`: apply or list pending migrations, then exit.
+async fn migrate(config: &Config, dry_run: bool) -> Result<(), Error> {
+    let db = utils::db::connect(&config.database.uri).await?;
+    let migrations = if dry_run {
+        migrations::pending(&db).await?
+    } else {
+        migrations::run(&db).await?
+    };
+
+    if migrations.is_empty() {
+        println!("No pending migrations");
+    }
+    for migration in migrations {
+        if dry_run {
+            println!("Pending: {}", migration);
+        } else {
+            println!("Applied: {}", migration);
+        }
+    }
+    Ok(())
+}
+
+/// `InvitationBot import <file> [--dry-run]`: load invite history from another
+/// tracker, as CSV or (for `.json` files) JSON, then exit.
+async fn import(config: &Config, path: &str, dry_run: bool) -> Result<(), Error> {
+    let input = std::fs::read_to_string(path)?;
+    let invites = if path.ends_with(".json") {
+        utils::import::from_json(&input)?
+    } else {
+        utils::import::from_csv(&input)?
+    };
+
+    let (invites, unknown): (Vec<_>, Vec<_>) = invites.into_iter().partition(|invite| {
+        config
+            .guilds
+            .allowed
+            .iter()
+            .any(|g| g.id == invite.guild_id)
+    });
+    for invite in &unknown {
+        println!(
+            "Skipped {} in unconfigured guild {}",
+            invite.invitee_id, invite.guild_id
+        );
+    }
+
+    if dry_run {
+        println!("Would import {} invites", invites.len());
+        return Ok(());
+    }
+
+    let db = utils::db::create_pool(&config.database.uri).await?;
+    let mut imported = 0;
+    for invite in &invites {
+        if utils::db::import_invite(&db, invite).await? {
+            imported += 1;
+        }
+    }
+    println!(
+        "Imported {} invites, {} already present",
+        imported,
+        invites.len() - imported
+    );
+    Ok(())
+}
+
 struct Handler {
     data: Data,
 }
 
 #[async_trait]
 impl serenity::EventHandler for Handler {
+    async fn ready(&self, ctx: serenity::Context, _ready: serenity::Ready) {
+        handlers::invite_events::seed_invite_cache(&ctx, &self.data).await;
+    }
+
+    async fn invite_create(&self, _ctx: serenity::Context, data: serenity::InviteCreateEvent) {
+        handlers::invite_events::handle_invite_create(&data, &self.data).await;
+    }
+
+    async fn invite_delete(&self, _ctx: serenity::Context, data: serenity::InviteDeleteEvent) {
+        handlers::invite_events::handle_invite_delete(&data, &self.data).await;
+    }
+
     async fn guild_member_addition(&self, ctx: serenity::Context, new_member: serenity::Member) {
         handlers::guild_member_add::handle_guild_member_add(
             &ctx,
@@ -152,6 +254,35 @@ impl serenity::EventHandler for Handler {
         )
         .await;
     }
+
+    async fn guild_ban_addition(
+        &self,
+        ctx: serenity::Context,
+        guild_id: serenity::GuildId,
+        banned_user: serenity::User,
+    ) {
+        handlers::guild_ban_add::handle_guild_ban_add(&ctx, guild_id, &banned_user, &self.data)
+            .await;
+    }
+
+    async fn interaction_create(&self, ctx: serenity::Context, interaction: serenity::Interaction) {
+        if let serenity::Interaction::Component(component) = interaction {
+            handlers::invite_approval::handle_approval_button(&ctx, &component, &self.data).await;
+        }
+    }
+
+    async fn guild_member_removal(
+        &self,
+        ctx: serenity::Context,
+        guild_id: serenity::GuildId,
+        user: serenity::User,
+        _member: Option<serenity::Member>,
+    ) {
+        handlers::guild_member_remove::handle_guild_member_remove(
+            &ctx, guild_id, &user, &self.data,
+        )
+        .await;
+    }
 }
 
 #[macro_export]
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS join_attributions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    invite_codes TEXT NOT NULL DEFAULT '',
+    joined_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_join_attributions_guild_user
+    ON join_attributions (guild_id, user_id);
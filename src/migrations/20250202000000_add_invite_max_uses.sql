@@ -0,0 +1,5 @@
+-- Uses allowed on the Discord invite; rows with parent_id record extra uses of a multi-use link
+ALTER TABLE invites ADD COLUMN max_uses INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE invites ADD COLUMN parent_id TEXT REFERENCES invites (id);
+
+CREATE INDEX IF NOT EXISTS idx_invites_discord_invite_code ON invites (discord_invite_code);
@@ -0,0 +1,2 @@
+ALTER TABLE invites ADD COLUMN revoked_at DATETIME;
+ALTER TABLE invites ADD COLUMN revoked_by TEXT;
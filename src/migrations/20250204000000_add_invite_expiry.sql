@@ -0,0 +1,2 @@
+-- When the bot link itself stops being redeemable, NULL for links that never expire
+ALTER TABLE invites ADD COLUMN expires_at DATETIME;
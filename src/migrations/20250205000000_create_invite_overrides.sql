@@ -0,0 +1,14 @@
+-- Per-member exceptions to the role-based invite limit, one per member and guild
+CREATE TABLE IF NOT EXISTS invite_overrides (
+    guild_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    -- 'extra', 'limit' or 'deny'
+    kind TEXT NOT NULL,
+    count INTEGER,
+    days INTEGER,
+    created_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- NULL for overrides that never expire
+    expires_at DATETIME,
+    PRIMARY KEY (guild_id, user_id)
+);
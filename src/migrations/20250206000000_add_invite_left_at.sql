@@ -0,0 +1,2 @@
+-- When the invitee left the guild, NULL while they are still a member
+ALTER TABLE invites ADD COLUMN left_at DATETIME;
@@ -0,0 +1,2 @@
+-- Comma-separated alt-account signals of a flagged join, NULL for clean joins
+ALTER TABLE invites ADD COLUMN flags TEXT;
@@ -0,0 +1,2 @@
+-- Where a row came from: NULL for links the bot issued, 'imported' for history loaded from another tracker
+ALTER TABLE invites ADD COLUMN source TEXT;
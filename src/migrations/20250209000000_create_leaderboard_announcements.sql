@@ -0,0 +1,5 @@
+-- When each guild's scheduled leaderboard was last posted, so restarts don't post it twice
+CREATE TABLE IF NOT EXISTS leaderboard_announcements (
+    guild_id TEXT PRIMARY KEY,
+    last_posted_at DATETIME NOT NULL
+);
@@ -0,0 +1,5 @@
+-- 'pending', 'approved' or 'denied' for links that need a moderator's approval, NULL otherwise
+ALTER TABLE invites ADD COLUMN approval_status TEXT;
+-- The requester's reason or intended invitee, shown to reviewers
+ALTER TABLE invites ADD COLUMN approval_note TEXT;
+ALTER TABLE invites ADD COLUMN reviewed_by TEXT;
@@ -0,0 +1,2 @@
+-- The user ID or username a link is reserved for, NULL for links anyone may use
+ALTER TABLE invites ADD COLUMN intended_for TEXT;
@@ -0,0 +1,2 @@
+-- Comma-separated role IDs given to whoever joins through the link, taken from the creator's allowed roles
+ALTER TABLE invites ADD COLUMN grant_roles TEXT;
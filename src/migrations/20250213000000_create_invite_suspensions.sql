@@ -0,0 +1,11 @@
+-- Members barred from creating invites for a while after an invitee was banned.
+-- Kept apart from invite_overrides so a suspension doesn't replace a member's
+-- grant or quota penalty, which apply again once it ends
+CREATE TABLE IF NOT EXISTS invite_suspensions (
+    guild_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    suspended_until DATETIME NOT NULL,
+    created_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (guild_id, user_id)
+);
@@ -0,0 +1,3 @@
+-- When the expiry sweep deleted the link's Discord invite, NULL until then.
+-- The code itself is kept, since used links still show it in /inviter and exports
+ALTER TABLE invites ADD COLUMN swept_at DATETIME;
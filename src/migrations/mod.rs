@@ -1,4 +1,7 @@
 use rust_embed::RustEmbed;
+use sha2::{Digest, Sha256};
+use sqlx::{Row, SqlitePool};
+use std::fmt;
 
 #[derive(RustEmbed)]
 #[folder = "src/migrations"]
@@ -9,13 +12,113 @@ pub fn get_migration(name: &str) -> Option<String> {
     Migrations::get(name).map(|f| String::from_utf8_lossy(f.data.as_ref()).into_owned())
 }
 
+/// Returns the embedded migration files in filename order.
 pub fn get_migrations() -> Vec<String> {
-    Migrations::iter().map(|f| f.to_string()).collect()
+    let mut migrations: Vec<String> = Migrations::iter().map(|f| f.to_string()).collect();
+    migrations.sort();
+    migrations
+}
+
+pub fn checksum(sql: &str) -> String {
+    Sha256::digest(sql.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    Database(sqlx::Error),
+    /// An already applied migration file was modified after it ran.
+    ChecksumMismatch(String),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::Database(e) => write!(f, "database error: {}", e),
+            MigrationError::ChecksumMismatch(name) => {
+                write!(f, "migration {} was modified after it was applied", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<sqlx::Error> for MigrationError {
+    fn from(e: sqlx::Error) -> Self {
+        MigrationError::Database(e)
+    }
+}
+
+async fn ensure_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version TEXT PRIMARY KEY,
+            checksum TEXT NOT NULL,
+            applied_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Lists migrations that have not been applied yet, failing if an applied one changed.
+pub async fn pending(pool: &SqlitePool) -> Result<Vec<String>, MigrationError> {
+    ensure_table(pool).await?;
+
+    let applied = sqlx::query("SELECT version, checksum FROM schema_migrations")
+        .fetch_all(pool)
+        .await?;
+
+    let mut pending = Vec::new();
+    for name in get_migrations() {
+        let sql = get_migration(&name).unwrap_or_default();
+        match applied
+            .iter()
+            .find(|row| row.get::<String, _>("version") == name)
+        {
+            Some(row) if row.get::<String, _>("checksum") != checksum(&sql) => {
+                return Err(MigrationError::ChecksumMismatch(name));
+            }
+            Some(_) => {}
+            None => pending.push(name),
+        }
+    }
+    Ok(pending)
+}
+
+/// Applies pending migrations in filename order, each inside its own transaction.
+pub async fn run(pool: &SqlitePool) -> Result<Vec<String>, MigrationError> {
+    let pending = pending(pool).await?;
+
+    for name in &pending {
+        let sql = get_migration(name).unwrap_or_default();
+        let mut tx = pool.begin().await?;
+        sqlx::raw_sql(&sql).execute(&mut *tx).await?;
+        sqlx::query("INSERT INTO schema_migrations (version, checksum) VALUES (?, ?)")
+            .bind(name)
+            .bind(checksum(&sql))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        log::info!("Applied migration {}", name);
+    }
+
+    Ok(pending)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use uuid::Uuid;
+
+    async fn connect() -> SqlitePool {
+        let db_url = format!("sqlite:file:{}?mode=memory", Uuid::new_v4());
+        SqlitePool::connect(&db_url).await.unwrap()
+    }
 
     #[test]
     fn test_get_migration() {
@@ -33,5 +136,37 @@ mod tests {
         let migrations = get_migrations();
         assert!(!migrations.is_empty());
         assert!(migrations.contains(&"20240101000000_create_tables.sql".to_string()));
+
+        let mut sorted = migrations.clone();
+        sorted.sort();
+        assert_eq!(migrations, sorted);
+    }
+
+    #[tokio::test]
+    async fn test_run_applies_once() {
+        let pool = connect().await;
+
+        assert_eq!(pending(&pool).await.unwrap(), get_migrations());
+        assert_eq!(run(&pool).await.unwrap(), get_migrations());
+
+        // A second run has nothing left to do
+        assert!(pending(&pool).await.unwrap().is_empty());
+        assert!(run(&pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_checksum_mismatch() {
+        let pool = connect().await;
+        run(&pool).await.unwrap();
+
+        sqlx::query("UPDATE schema_migrations SET checksum = 'tampered'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            pending(&pool).await,
+            Err(MigrationError::ChecksumMismatch(_))
+        ));
     }
 }
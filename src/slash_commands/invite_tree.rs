@@ -0,0 +1,93 @@
+use crate::{t, Context, Error};
+use poise::serenity_prelude::{CreateEmbed, CreateEmbedFooter, User};
+use poise::CreateReply;
+use std::collections::HashMap;
+
+/// How many levels to follow up and down the chain.
+const MAX_DEPTH: usize = 10;
+const LINES_PER_PAGE: usize = 20;
+
+/// View a user's invite chain
+#[poise::command(slash_command, guild_only, rename = "invite-tree")]
+pub async fn invite_tree(
+    ctx: Context<'_>,
+    #[description = "User to check"] user: User,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().to_string();
+    let config = ctx.data().config.get();
+    let locale = config.get_guild_locale(&guild_id);
+    let user_id = user.id.to_string();
+
+    // Walk up to the root, stopping at a cycle
+    let mut ancestors = Vec::new();
+    let mut current = user_id.clone();
+    while ancestors.len() < MAX_DEPTH {
+        let inviter = crate::utils::db::get_user_invite_info(&ctx.data().db, &guild_id, &current)
+            .await?
+            .and_then(|info| info.creator_id);
+        match inviter {
+            Some(inviter) if inviter != user_id && !ancestors.contains(&inviter) => {
+                current = inviter.clone();
+                ancestors.push(inviter);
+            }
+            _ => break,
+        }
+    }
+    ancestors.reverse();
+
+    let invitees =
+        crate::utils::db::get_invitee_tree(&ctx.data().db, &guild_id, &user_id, MAX_DEPTH as i64)
+            .await?;
+
+    let mut params = HashMap::new();
+    params.insert("user", format!("<@{}>", user.id));
+    params.insert("name", user.name.clone());
+    params.insert("count", invitees.len().to_string());
+
+    if ancestors.is_empty() && invitees.is_empty() {
+        let embed = CreateEmbed::default()
+            .title(t!(locale, "commands.invite_tree.errors.no_record.title"))
+            .description(t!(
+                locale,
+                "commands.invite_tree.errors.no_record.description",
+                params
+            ))
+            .color(0xFF3333)
+            .footer(CreateEmbedFooter::new(t!(
+                locale,
+                "commands.invite_tree.errors.no_record.footer"
+            )));
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+        return Ok(());
+    }
+
+    let lines = crate::utils::invite_tree::render(&ancestors, &user_id, &invitees);
+    let page_count = lines.len().div_ceil(LINES_PER_PAGE);
+    let pages = lines
+        .chunks(LINES_PER_PAGE)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let mut params = params.clone();
+            params.insert("page", (index + 1).to_string());
+            params.insert("pages", page_count.to_string());
+
+            CreateEmbed::default()
+                .title(t!(
+                    locale,
+                    "commands.invite_tree.success.title",
+                    params.clone()
+                ))
+                .description(chunk.join("\n"))
+                .color(0x4CACEE)
+                .footer(CreateEmbedFooter::new(t!(
+                    locale,
+                    "commands.invite_tree.success.footer",
+                    params
+                )))
+        })
+        .collect();
+
+    crate::utils::pagination::paginate(ctx, pages, true).await
+}
@@ -11,33 +11,39 @@ pub async fn inviter(
     #[description = "User to check"] user: User,
 ) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap();
-    let locale = ctx.data().config.get_guild_locale(&guild_id.to_string());
+    let config = ctx.data().config.get();
+    let locale = config.get_guild_locale(&guild_id.to_string());
 
-    let invite_info =
-        match crate::utils::db::get_user_invite_info(&ctx.data().db, &user.id.to_string()).await? {
-            Some(info) => info,
-            None => {
-                let mut params = HashMap::new();
-                params.insert("user", format!("<@{}>", user.id));
+    let invite_info = match crate::utils::db::get_user_invite_info(
+        &ctx.data().db,
+        &guild_id.to_string(),
+        &user.id.to_string(),
+    )
+    .await?
+    {
+        Some(info) => info,
+        None => {
+            let mut params = HashMap::new();
+            params.insert("user", format!("<@{}>", user.id));
 
-                let embed = CreateEmbed::default()
-                    .title(t!(locale, "commands.inviter.errors.no_record.title"))
-                    .description(t!(
-                        locale,
-                        "commands.inviter.errors.no_record.description",
-                        params
-                    ))
-                    .color(0xFF3333)
-                    .footer(CreateEmbedFooter::new(t!(
-                        locale,
-                        "commands.inviter.errors.no_record.footer"
-                    )));
+            let embed = CreateEmbed::default()
+                .title(t!(locale, "commands.inviter.errors.no_record.title"))
+                .description(t!(
+                    locale,
+                    "commands.inviter.errors.no_record.description",
+                    params
+                ))
+                .color(0xFF3333)
+                .footer(CreateEmbedFooter::new(t!(
+                    locale,
+                    "commands.inviter.errors.no_record.footer"
+                )));
 
-                ctx.send(CreateReply::default().embed(embed).ephemeral(true))
-                    .await?;
-                return Ok(());
-            }
-        };
+            ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+                .await?;
+            return Ok(());
+        }
+    };
 
     let creator = match invite_info.creator_id.unwrap().parse() {
         Ok(id) => ctx.http().get_user(id).await?,
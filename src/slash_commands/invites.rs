@@ -1,14 +1,37 @@
-use crate::utils::config::AllowedRole;
+use crate::utils::config::{AllowedGuild, EffectiveLimit, InviteLimit, LimitPolicy, Retention};
+use crate::utils::db::NewInvite;
+use crate::utils::overrides::LimitOverride;
+use crate::utils::revoke::{parse_invite_id, RevokeError, Revoker};
 use crate::{t, Context, Error};
 use chrono::{Duration, Utc};
-use poise::serenity_prelude::{CreateEmbed, CreateEmbedFooter};
+use log::warn;
+use poise::serenity_prelude::{CreateEmbed, CreateEmbedFooter, Member, User, UserId};
 use poise::CreateReply;
 use std::collections::HashMap;
 use uuid::Uuid;
 
+/// Manage your invite links
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands("create", "revoke", "stats"),
+    subcommand_required
+)]
+pub async fn invites(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
 /// Create an invite link
 #[poise::command(slash_command, guild_only)]
-pub async fn invites(ctx: Context<'_>) -> Result<(), Error> {
+pub async fn create(
+    ctx: Context<'_>,
+    #[description = "Reason or who the invite is for, shown to moderators if approval is required"]
+    #[max_length = 200]
+    note: Option<String>,
+    #[rename = "for"]
+    #[description = "Username or user ID of the only account that should join with this link"]
+    intended_for: Option<String>,
+) -> Result<(), Error> {
     let guild_id = match ctx.guild_id() {
         Some(id) => id,
         None => {
@@ -26,12 +49,11 @@ pub async fn invites(ctx: Context<'_>) -> Result<(), Error> {
     };
 
     let member = ctx.author_member().await.unwrap_or_default();
-    let locale = ctx.data().config.get_guild_locale(&guild_id.to_string());
+    let config = ctx.data().config.get();
+    let locale = config.get_guild_locale(&guild_id.to_string());
 
     // Validate guild configuration
-    let guild_config = match ctx
-        .data()
-        .config
+    let guild_config = match config
         .guilds
         .allowed
         .iter()
@@ -48,7 +70,7 @@ pub async fn invites(ctx: Context<'_>) -> Result<(), Error> {
     let min_stay_duration = Duration::seconds(
         guild_config
             .min_member_age
-            .unwrap_or(ctx.data().config.bot.default_min_member_age) as i64,
+            .unwrap_or(config.bot.default_min_member_age) as i64,
     );
 
     if let Some(join_date) = member.joined_at {
@@ -65,59 +87,176 @@ pub async fn invites(ctx: Context<'_>) -> Result<(), Error> {
         return Ok(());
     }
 
-    // Validate member roles
-    let role_with_limit = match member.roles.iter().find_map(|role_id| {
-        guild_config
-            .allowed_roles
-            .iter()
-            .find(|allowed_role| allowed_role.id == role_id.to_string())
-    }) {
-        Some(role) => role,
+    // Validate member roles, then apply any moderator override
+    let member_limit = get_member_limit(ctx, guild_config, &member).await?;
+    let limit = match member_limit.limit() {
+        Some(limit) => limit,
+        None if member_limit.limit_override == Some(LimitOverride::Deny) => {
+            send_error_embed(ctx, locale, "commands.invites.errors.denied").await?;
+            return Ok(());
+        }
         None => {
             send_error_embed(ctx, locale, "commands.invites.errors.missing_permissions").await?;
             return Ok(());
         }
     };
 
-    // Check invite usage limit
+    // Check invite usage limit. Every invitee counts, whatever count_mode says, so
+    // leavers don't hand the quota back
     let used_invites = crate::utils::db::count_used_invites(
         &ctx.data().db,
         &ctx.author().id.to_string(),
         &guild_id.to_string(),
-        role_with_limit.invite_limit.days,
+        limit.days,
+        Retention::All,
     )
     .await?;
 
-    if used_invites >= role_with_limit.invite_limit.count as i64 {
-        let params = create_limit_params(role_with_limit, used_invites);
+    if used_invites >= limit.count as i64 {
+        let params = create_limit_params(&limit, used_invites);
         send_limit_reached_embed(ctx, locale, params).await?;
         return Ok(());
     }
 
+    // Check outstanding (unused, unexpired) links
+    let outstanding_invites = crate::utils::db::count_outstanding_invites(
+        &ctx.data().db,
+        &ctx.author().id.to_string(),
+        &guild_id.to_string(),
+    )
+    .await?;
+
+    if let Some(max_outstanding) = guild_config.max_outstanding {
+        if outstanding_invites >= max_outstanding as i64 {
+            let params = create_outstanding_params(outstanding_invites, max_outstanding);
+            send_too_many_outstanding_embed(ctx, locale, params).await?;
+            return Ok(());
+        }
+    }
+
+    // Reserve the link for one account, checked again when they join
+    let intended_for = match intended_for.as_deref() {
+        Some(input) => match crate::utils::reservation::normalize(input) {
+            Some(intended_for) => Some(intended_for),
+            None => {
+                send_error_embed(ctx, locale, "commands.invites.errors.invalid_intended_for")
+                    .await?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    if let Some(user_id) = intended_for
+        .as_deref()
+        .and_then(crate::utils::reservation::user_id)
+    {
+        if UserId::new(user_id).to_user(ctx).await.is_err() {
+            send_error_embed(ctx, locale, "commands.invites.errors.invalid_intended_for").await?;
+            return Ok(());
+        }
+    }
+    let reserved_for = intended_for.as_deref().map(|intended_for| {
+        t!(
+            locale,
+            "commands.invites.reserved_for",
+            HashMap::from([("user", crate::utils::reservation::display(intended_for))])
+        )
+    });
+
+    let require_approval = guild_config.require_approval.unwrap_or(false);
+    if require_approval && guild_config.review_channel().is_none() {
+        send_error_embed(ctx, locale, "commands.invites.errors.no_review_channel").await?;
+        return Ok(());
+    }
+
+    // Roles the inviter's allowed roles hand out to their invitees
+    let role_ids: Vec<String> = member.roles.iter().map(|id| id.to_string()).collect();
+    let grant_roles = guild_config.grant_roles_on_join(&role_ids).join(",");
+
     // Create and record invite
     let invite_id = Uuid::new_v4().to_string();
     crate::utils::db::create_invite(
         &ctx.data().db,
-        &invite_id,
-        &guild_id.to_string(),
-        &ctx.author().id.to_string(),
+        &NewInvite {
+            id: &invite_id,
+            guild_id: &guild_id.to_string(),
+            creator_id: &ctx.author().id.to_string(),
+            // A reserved link admits a single account
+            max_uses: if intended_for.is_some() {
+                1
+            } else {
+                guild_config.invite_max_uses()
+            },
+            link_ttl: guild_config.link_ttl,
+            pending_approval: require_approval,
+            approval_note: note.as_deref(),
+            intended_for: intended_for.as_deref(),
+            grant_roles: (!grant_roles.is_empty()).then_some(grant_roles.as_str()),
+        },
     )
     .await?;
 
-    let bot_invite_url = format!(
-        "{}/invite/{}",
-        ctx.data().config.server.external_url,
-        invite_id
-    );
+    let bot_invite_url = format!("{}/invite/{}", config.server.external_url, invite_id);
+
+    if require_approval {
+        if let Err(e) = crate::utils::approval::post_request(
+            ctx.http(),
+            guild_config,
+            locale,
+            ctx.author(),
+            &invite_id,
+            note.as_deref(),
+            intended_for.as_deref(),
+        )
+        .await
+        {
+            // Nobody could ever review the link, so don't leave it taking up a slot
+            warn!("Failed to post approval request {}: {}", invite_id, e);
+            let bot_id = ctx.cache().current_user().id.to_string();
+            crate::utils::db::revoke_invite(&ctx.data().db, &invite_id, &bot_id).await?;
+            send_error_embed(ctx, locale, "commands.invites.errors.review_failed").await?;
+            return Ok(());
+        }
+
+        let mut params = HashMap::new();
+        params.insert("guild", guild.name.clone());
+        params.insert("url", bot_invite_url);
+        let mut description = t!(locale, "commands.invites.pending.description", params);
+        if let Some(reserved_for) = &reserved_for {
+            description.push_str(&format!("\n{}", reserved_for));
+        }
+        let embed = CreateEmbed::default()
+            .title(t!(locale, "commands.invites.pending.title"))
+            .description(description)
+            .color(0xFFA500)
+            .thumbnail(guild.icon_url().unwrap_or_default())
+            .footer(CreateEmbedFooter::new(t!(
+                locale,
+                "commands.invites.pending.footer"
+            )));
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+        return Ok(());
+    }
 
     let guild_name = guild.name.clone();
+    let quota = QuotaStatus {
+        limit,
+        role_limit: member_limit.role_limit.as_ref(),
+        limit_override: member_limit.limit_override,
+        used_invites,
+        // Including the link just created
+        outstanding_invites: outstanding_invites + 1,
+        max_outstanding: guild_config.max_outstanding,
+    };
     send_success_embed(
         ctx,
         locale,
         guild_name,
-        role_with_limit,
-        used_invites,
+        &quota,
         bot_invite_url,
+        reserved_for,
         guild.icon_url(),
     )
     .await?;
@@ -125,6 +264,243 @@ pub async fn invites(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+/// Revoke an invite link you created
+#[poise::command(slash_command, guild_only)]
+pub async fn revoke(
+    ctx: Context<'_>,
+    #[description = "Invite ID or link"] id: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let config = ctx.data().config.get();
+    let locale = config.get_guild_locale(&guild_id.to_string());
+
+    let is_admin = match (
+        ctx.author_member().await,
+        config
+            .guilds
+            .allowed
+            .iter()
+            .find(|g| g.id == guild_id.to_string()),
+    ) {
+        (Some(member), Some(guild_config)) => {
+            crate::utils::permissions::is_admin(&member, guild_config)
+        }
+        _ => false,
+    };
+
+    let invite_id = parse_invite_id(&id);
+    let result = crate::utils::revoke::revoke_invite(
+        ctx.http(),
+        &ctx.data().db,
+        &ctx.data().invites,
+        invite_id,
+        Revoker {
+            user_id: &ctx.author().id.to_string(),
+            is_admin,
+            guild_id: Some(&guild_id.to_string()),
+        },
+    )
+    .await;
+
+    let error_key = match result {
+        Ok(()) => {
+            let mut params = HashMap::new();
+            params.insert("id", invite_id.to_string());
+
+            let embed = CreateEmbed::default()
+                .title(t!(locale, "commands.invites.revoke.success.title"))
+                .description(t!(
+                    locale,
+                    "commands.invites.revoke.success.description",
+                    params
+                ))
+                .color(0x4CACEE)
+                .footer(CreateEmbedFooter::new(t!(
+                    locale,
+                    "commands.invites.revoke.success.footer"
+                )));
+
+            ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+                .await?;
+            return Ok(());
+        }
+        Err(RevokeError::NotFound) => "commands.invites.revoke.errors.not_found",
+        Err(RevokeError::Forbidden) => "commands.invites.revoke.errors.forbidden",
+        Err(RevokeError::AlreadyUsed) => "commands.invites.revoke.errors.already_used",
+        Err(RevokeError::AlreadyRevoked) => "commands.invites.revoke.errors.already_revoked",
+        Err(RevokeError::Database(e)) => return Err(e.into()),
+    };
+
+    send_error_embed(ctx, locale, error_key).await
+}
+
+/// View invite statistics
+#[poise::command(slash_command, guild_only)]
+pub async fn stats(
+    ctx: Context<'_>,
+    #[description = "User to check (default: yourself)"] user: Option<User>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let config = ctx.data().config.get();
+    let locale = config.get_guild_locale(&guild_id.to_string());
+    let user = user.unwrap_or_else(|| ctx.author().clone());
+    let user_id = user.id.to_string();
+
+    let guild_config = match config
+        .guilds
+        .allowed
+        .iter()
+        .find(|g| g.id == guild_id.to_string())
+    {
+        Some(config) => config,
+        None => {
+            send_error_embed(ctx, locale, "commands.invites.errors.server_not_allowed").await?;
+            return Ok(());
+        }
+    };
+
+    let stats =
+        crate::utils::db::get_invite_stats(&ctx.data().db, &guild_id.to_string(), &user_id).await?;
+    let recent = crate::utils::db::get_recent_invitees(
+        &ctx.data().db,
+        &guild_id.to_string(),
+        &user_id,
+        RECENT_INVITEES,
+    )
+    .await?;
+
+    let mut params = HashMap::new();
+    params.insert("user", format!("<@{}>", user.id));
+    params.insert("created", stats.created.to_string());
+    params.insert("used", stats.used.to_string());
+    params.insert("pending", stats.pending.to_string());
+    params.insert("expired", stats.expired.to_string());
+    params.insert("revoked", stats.revoked.to_string());
+    params.insert("retained", stats.retained.to_string());
+
+    // Members who left can't have a quota, so only show it for current ones
+    let quota = match guild_id.member(ctx, user.id).await {
+        Ok(member) => {
+            let member_limit = get_member_limit(ctx, guild_config, &member).await?;
+            match member_limit.limit() {
+                Some(limit) => {
+                    let used = crate::utils::db::count_used_invites(
+                        &ctx.data().db,
+                        &user_id,
+                        &guild_id.to_string(),
+                        limit.days,
+                        Retention::All,
+                    )
+                    .await?;
+                    t!(
+                        locale,
+                        "commands.invites.stats.quota.window",
+                        create_limit_params(&limit, used)
+                    )
+                }
+                None if member_limit.limit_override == Some(LimitOverride::Deny) => {
+                    t!(locale, "commands.invites.stats.quota.denied")
+                }
+                None => t!(locale, "commands.invites.stats.quota.no_role"),
+            }
+        }
+        Err(_) => t!(locale, "commands.invites.stats.quota.not_member"),
+    };
+
+    let recent = if recent.is_empty() {
+        t!(locale, "commands.invites.stats.no_invitees")
+    } else {
+        recent
+            .iter()
+            .map(|invitee| {
+                let mut line = format!(
+                    "• <@{}> <t:{}:d>",
+                    invitee.user_id,
+                    invitee.used_at.unix_timestamp()
+                );
+                if invitee.left_at.is_some() {
+                    line.push_str(&format!(" ({})", t!(locale, "commands.invites.stats.left")));
+                }
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "commands.invites.stats.title"))
+        .description(format!(
+            "{}\n\n**{}**:\n• {}\n• {}\n• {}\n\n**{}**:\n{}\n\n**{}**:\n{}",
+            t!(locale, "commands.invites.stats.description", params.clone()),
+            t!(locale, "commands.invites.stats.links"),
+            t!(locale, "commands.invites.stats.link_counts", params.clone()),
+            t!(
+                locale,
+                "commands.invites.stats.link_statuses",
+                params.clone()
+            ),
+            t!(locale, "commands.invites.stats.retained", params),
+            t!(locale, "commands.invites.stats.quota.title"),
+            quota,
+            t!(locale, "commands.invites.stats.recent"),
+            recent,
+        ))
+        .color(0x4CACEE)
+        .thumbnail(user.face())
+        .footer(CreateEmbedFooter::new(t!(
+            locale,
+            "commands.invites.stats.footer"
+        )));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+/// How many invitees `/invites stats` lists.
+const RECENT_INVITEES: i64 = 5;
+
+/// A member's invite limit, from their roles and any moderator override.
+struct MemberLimit<'a> {
+    role_limit: Option<EffectiveLimit<'a>>,
+    limit_override: Option<LimitOverride>,
+}
+
+impl MemberLimit<'_> {
+    /// `None` if the member may not create invites.
+    fn limit(&self) -> Option<InviteLimit> {
+        let base_limit = self.role_limit.as_ref().map(|effective| effective.limit);
+        match self.limit_override {
+            Some(limit_override) => limit_override.apply(base_limit),
+            None => base_limit,
+        }
+    }
+}
+
+async fn get_member_limit<'a>(
+    ctx: Context<'_>,
+    guild_config: &'a AllowedGuild,
+    member: &Member,
+) -> Result<MemberLimit<'a>, Error> {
+    let role_ids: Vec<String> = member.roles.iter().map(|id| id.to_string()).collect();
+    let user_id = member.user.id.to_string();
+    // A suspension outranks the override, which applies again once it ends
+    let limit_override =
+        if crate::utils::db::is_suspended(&ctx.data().db, &guild_config.id, &user_id).await? {
+            Some(LimitOverride::Deny)
+        } else {
+            crate::utils::db::get_active_override(&ctx.data().db, &guild_config.id, &user_id)
+                .await?
+                .as_ref()
+                .and_then(LimitOverride::from_record)
+        };
+
+    Ok(MemberLimit {
+        role_limit: guild_config.effective_limit(&role_ids),
+        limit_override,
+    })
+}
+
 async fn send_error_embed(ctx: Context<'_>, locale: &str, error_key: &str) -> Result<(), Error> {
     let embed = CreateEmbed::default()
         .title(t!(locale, format!("{}.title", error_key).as_str()))
@@ -201,33 +577,109 @@ async fn send_not_long_enough_embed(
     Ok(())
 }
 
+async fn send_too_many_outstanding_embed(
+    ctx: Context<'_>,
+    locale: &str,
+    params: HashMap<&str, String>,
+) -> Result<(), Error> {
+    let embed = CreateEmbed::default()
+        .title(t!(
+            locale,
+            "commands.invites.errors.too_many_outstanding.title"
+        ))
+        .description(t!(
+            locale,
+            "commands.invites.errors.too_many_outstanding.description",
+            params
+        ))
+        .color(0xFF3333)
+        .footer(CreateEmbedFooter::new(t!(
+            locale,
+            "commands.invites.errors.too_many_outstanding.footer"
+        )));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+/// A member's quota at the time a link is created.
+struct QuotaStatus<'a> {
+    limit: InviteLimit,
+    role_limit: Option<&'a EffectiveLimit<'a>>,
+    limit_override: Option<LimitOverride>,
+    used_invites: i64,
+    outstanding_invites: i64,
+    max_outstanding: Option<u32>,
+}
+
 async fn send_success_embed(
     ctx: Context<'_>,
     locale: &str,
     guild_name: String,
-    role_with_limit: &AllowedRole,
-    used_invites: i64,
+    quota: &QuotaStatus<'_>,
     bot_invite_url: String,
+    reserved_for: Option<String>,
     guild_icon_url: Option<String>,
 ) -> Result<(), Error> {
-    let params = create_success_params(role_with_limit, used_invites, &guild_name);
+    let params = create_success_params(quota, &guild_name);
+    let mut limits = vec![
+        t!(
+            locale,
+            "commands.invites.success.invites_per_days",
+            params.clone()
+        ),
+        t!(
+            locale,
+            "commands.invites.success.used_remaining",
+            params.clone()
+        ),
+        t!(
+            locale,
+            "commands.invites.success.outstanding",
+            params.clone()
+        ),
+    ];
+    match (quota.limit_override, quota.role_limit) {
+        (Some(LimitOverride::Limit(_)), _) => {
+            limits.push(t!(locale, "commands.invites.success.override.limit"))
+        }
+        (limit_override, Some(role_limit)) => {
+            limits.push(t!(
+                locale,
+                policy_explanation_key(role_limit),
+                params.clone()
+            ));
+            match limit_override {
+                Some(LimitOverride::Extra(extra)) if extra < 0 => limits.push(t!(
+                    locale,
+                    "commands.invites.success.override.penalty",
+                    params.clone()
+                )),
+                Some(LimitOverride::Extra(_)) => limits.push(t!(
+                    locale,
+                    "commands.invites.success.override.extra",
+                    params.clone()
+                )),
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+
+    let mut link = bot_invite_url;
+    if let Some(reserved_for) = reserved_for {
+        link.push_str(&format!("\n{}", reserved_for));
+    }
+
     let embed = CreateEmbed::default()
         .title(t!(locale, "commands.invites.success.title"))
         .description(format!(
-            "{}\n\n{}\n\n**{}**:\n• {}\n• {}",
-            t!(
-                locale,
-                "commands.invites.success.description",
-                params.clone()
-            ),
-            bot_invite_url,
+            "{}\n\n{}\n\n**{}**:\n• {}",
+            t!(locale, "commands.invites.success.description", params),
+            link,
             t!(locale, "commands.invites.success.limits"),
-            t!(
-                locale,
-                "commands.invites.success.invites_per_days",
-                params.clone()
-            ),
-            t!(locale, "commands.invites.success.used_remaining", params),
+            limits.join("\n• "),
         ))
         .color(0x4CACEE)
         .thumbnail(guild_icon_url.unwrap_or_default())
@@ -241,18 +693,24 @@ async fn send_success_embed(
     Ok(())
 }
 
-fn create_limit_params(role_with_limit: &AllowedRole, used_invites: i64) -> HashMap<&str, String> {
+fn create_limit_params<'a>(limit: &InviteLimit, used_invites: i64) -> HashMap<&'a str, String> {
     let mut params = HashMap::new();
-    params.insert("count", role_with_limit.invite_limit.count.to_string());
-    params.insert("days", role_with_limit.invite_limit.days.to_string());
+    params.insert("count", limit.count.to_string());
+    params.insert("days", limit.days.to_string());
     params.insert("used", used_invites.to_string());
-    params.insert(
-        "remaining",
-        (role_with_limit.invite_limit.count as i64 - used_invites).to_string(),
-    );
+    params.insert("remaining", (limit.count as i64 - used_invites).to_string());
     params
 }
 
+/// Which sentence explains how the limit was picked from the member's roles.
+fn policy_explanation_key(effective_limit: &EffectiveLimit<'_>) -> &'static str {
+    match effective_limit.policy {
+        LimitPolicy::Max => "commands.invites.success.policy.max",
+        LimitPolicy::Sum => "commands.invites.success.policy.sum",
+        LimitPolicy::Priority => "commands.invites.success.policy.priority",
+    }
+}
+
 fn create_not_long_enough_params<'a>(days: i64, remaining: i64) -> HashMap<&'a str, String> {
     let mut params = HashMap::new();
     params.insert("days", days.to_string());
@@ -260,19 +718,48 @@ fn create_not_long_enough_params<'a>(days: i64, remaining: i64) -> HashMap<&'a s
     params
 }
 
+fn create_outstanding_params<'a>(outstanding: i64, max: u32) -> HashMap<&'a str, String> {
+    let mut params = HashMap::new();
+    params.insert("outstanding", outstanding.to_string());
+    params.insert("max", max.to_string());
+    params
+}
+
 fn create_success_params<'a>(
-    role_with_limit: &'a AllowedRole,
-    used_invites: i64,
+    quota: &QuotaStatus<'a>,
     guild_name: &'a str,
 ) -> HashMap<&'a str, String> {
+    let limit = &quota.limit;
     let mut params = HashMap::new();
+    if let Some(role_limit) = quota.role_limit {
+        params.insert(
+            "roles",
+            role_limit
+                .roles
+                .iter()
+                .map(|role| format!("<@&{}>", role.id))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+    }
+    if let Some(LimitOverride::Extra(extra)) = quota.limit_override {
+        params.insert("extra", extra.to_string());
+        params.insert("penalty", (-extra).to_string());
+    }
     params.insert("guild", guild_name.to_string());
-    params.insert("count", role_with_limit.invite_limit.count.to_string());
-    params.insert("days", role_with_limit.invite_limit.days.to_string());
-    params.insert("used", used_invites.to_string());
+    params.insert("count", limit.count.to_string());
+    params.insert("days", limit.days.to_string());
+    params.insert("used", quota.used_invites.to_string());
     params.insert(
         "remaining",
-        (role_with_limit.invite_limit.count as i64 - used_invites).to_string(),
+        (limit.count as i64 - quota.used_invites).to_string(),
+    );
+    params.insert(
+        "outstanding",
+        match quota.max_outstanding {
+            Some(max) => format!("{}/{}", quota.outstanding_invites, max),
+            None => quota.outstanding_invites.to_string(),
+        },
     );
     params
 }
@@ -0,0 +1,496 @@
+use crate::utils::config::InviteLimit;
+use crate::utils::db::{InviteFilter, InviteListEntry, InviteStatus, NewOverride};
+use crate::utils::export::ExportFormat;
+use crate::utils::overrides::LimitOverride;
+use crate::{t, Context, Error};
+use chrono::NaiveDate;
+use poise::serenity_prelude::{CreateAttachment, CreateEmbed, CreateEmbedFooter, User};
+use poise::CreateReply;
+use std::collections::HashMap;
+
+/// Manage members' invite limits
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "invites-admin",
+    subcommands("grant", "deny", "reset", "list", "export"),
+    subcommand_required
+)]
+pub async fn invites_admin(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Give a member extra invites or a custom limit
+#[poise::command(slash_command, guild_only)]
+pub async fn grant(
+    ctx: Context<'_>,
+    #[description = "Member to grant invites to"] user: User,
+    #[description = "Invites on top of their role limit"]
+    #[min = 1]
+    #[max = 1000]
+    extra: Option<u32>,
+    #[description = "Custom limit replacing their role limit"]
+    #[min = 1]
+    #[max = 1000]
+    limit: Option<u32>,
+    #[description = "Days the custom limit covers (default: 30)"]
+    #[min = 1]
+    #[max = 3650]
+    days: Option<u32>,
+    #[description = "Days until the override lapses (default: never)"]
+    #[min = 1]
+    #[max = 3650]
+    expires_in: Option<u32>,
+) -> Result<(), Error> {
+    if !check_admin(ctx).await? {
+        return Ok(());
+    }
+    let locale = guild_locale(ctx);
+
+    let mut params = HashMap::new();
+    params.insert("user", format!("<@{}>", user.id));
+    params.insert("expiry", expiry_text(&locale, expires_in));
+
+    let (limit_override, key) = match (extra, limit) {
+        (Some(extra), None) => {
+            params.insert("extra", extra.to_string());
+            (
+                LimitOverride::Extra(i32::try_from(extra)?),
+                "commands.invites_admin.grant.success.extra",
+            )
+        }
+        (None, Some(count)) => {
+            let days = days.unwrap_or(30);
+            params.insert("count", count.to_string());
+            params.insert("days", days.to_string());
+            (
+                LimitOverride::Limit(InviteLimit {
+                    count: i32::try_from(count)?,
+                    days: i32::try_from(days)?,
+                }),
+                "commands.invites_admin.grant.success.limit",
+            )
+        }
+        _ => {
+            send_embed(
+                ctx,
+                &locale,
+                "commands.invites_admin.grant.errors.invalid",
+                HashMap::new(),
+                0xFF3333,
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    set_override(ctx, &user, limit_override, expires_in).await?;
+    send_embed(ctx, &locale, key, params, 0x4CACEE).await
+}
+
+/// Stop a member from creating invites
+#[poise::command(slash_command, guild_only)]
+pub async fn deny(
+    ctx: Context<'_>,
+    #[description = "Member to deny invites to"] user: User,
+    #[description = "Days until the ban lapses (default: never)"]
+    #[min = 1]
+    #[max = 3650]
+    expires_in: Option<u32>,
+) -> Result<(), Error> {
+    if !check_admin(ctx).await? {
+        return Ok(());
+    }
+    let locale = guild_locale(ctx);
+
+    set_override(ctx, &user, LimitOverride::Deny, expires_in).await?;
+
+    let mut params = HashMap::new();
+    params.insert("user", format!("<@{}>", user.id));
+    params.insert("expiry", expiry_text(&locale, expires_in));
+    send_embed(
+        ctx,
+        &locale,
+        "commands.invites_admin.deny.success",
+        params,
+        0x4CACEE,
+    )
+    .await
+}
+
+/// Put a member back on their role limit
+#[poise::command(slash_command, guild_only)]
+pub async fn reset(
+    ctx: Context<'_>,
+    #[description = "Member to reset"] user: User,
+) -> Result<(), Error> {
+    if !check_admin(ctx).await? {
+        return Ok(());
+    }
+    let locale = guild_locale(ctx);
+
+    let guild_id = ctx.guild_id().unwrap().to_string();
+    let user_id = user.id.to_string();
+    let removed = crate::utils::db::delete_override(&ctx.data().db, &guild_id, &user_id).await?;
+    let lifted = crate::utils::db::lift_suspension(&ctx.data().db, &guild_id, &user_id).await?;
+
+    let mut params = HashMap::new();
+    params.insert("user", format!("<@{}>", user.id));
+    if removed || lifted {
+        send_embed(
+            ctx,
+            &locale,
+            "commands.invites_admin.reset.success",
+            params,
+            0x4CACEE,
+        )
+        .await
+    } else {
+        send_embed(
+            ctx,
+            &locale,
+            "commands.invites_admin.reset.errors.not_found",
+            params,
+            0xFF3333,
+        )
+        .await
+    }
+}
+
+/// Links fetched per query while `list` pages through the matches.
+const LIST_BATCH: i64 = 200;
+const INVITES_PER_PAGE: usize = 10;
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum StatusChoice {
+    #[name = "Pending"]
+    Pending,
+    #[name = "Used"]
+    Used,
+    #[name = "Expired"]
+    Expired,
+    #[name = "Revoked"]
+    Revoked,
+}
+
+impl From<StatusChoice> for InviteStatus {
+    fn from(choice: StatusChoice) -> Self {
+        match choice {
+            StatusChoice::Pending => InviteStatus::Pending,
+            StatusChoice::Used => InviteStatus::Used,
+            StatusChoice::Expired => InviteStatus::Expired,
+            StatusChoice::Revoked => InviteStatus::Revoked,
+        }
+    }
+}
+
+/// List invite links in this server
+#[poise::command(slash_command, guild_only)]
+pub async fn list(
+    ctx: Context<'_>,
+    #[description = "Only links created by this member"] creator: Option<User>,
+    #[description = "Only links in this state"] status: Option<StatusChoice>,
+    #[description = "Created on or after this day (YYYY-MM-DD)"] from: Option<String>,
+    #[description = "Created on or before this day (YYYY-MM-DD)"] to: Option<String>,
+) -> Result<(), Error> {
+    if !check_admin(ctx).await? {
+        return Ok(());
+    }
+    let locale = guild_locale(ctx);
+    let guild_id = ctx.guild_id().unwrap().to_string();
+
+    let Some((created_from, created_to)) = parse_date_range(ctx, &locale, from, to).await? else {
+        return Ok(());
+    };
+
+    let creator_id = creator.map(|user| user.id.to_string());
+    let status = status.map(InviteStatus::from);
+    let mut invites: Vec<InviteListEntry> = Vec::new();
+    loop {
+        let batch = crate::utils::db::list_invites(
+            &ctx.data().db,
+            &InviteFilter {
+                guild_id: Some(&guild_id),
+                creator_id: creator_id.as_deref(),
+                status,
+                created_from,
+                created_to,
+                after: invites.last().map(|invite| invite.id.as_str()),
+            },
+            LIST_BATCH,
+        )
+        .await?;
+        let done = (batch.len() as i64) < LIST_BATCH;
+        invites.extend(batch);
+        if done {
+            break;
+        }
+    }
+
+    if invites.is_empty() {
+        send_embed(
+            ctx,
+            &locale,
+            "commands.invites_admin.list.errors.empty",
+            HashMap::new(),
+            0xFF3333,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let lines: Vec<String> = invites
+        .iter()
+        .map(|invite| {
+            t!(
+                &locale,
+                "commands.invites_admin.list.success.line",
+                HashMap::from([
+                    ("id", invite.id.clone()),
+                    ("creator", format!("<@{}>", invite.creator_id)),
+                    (
+                        "status",
+                        t!(
+                            &locale,
+                            format!("commands.invites_admin.list.status.{}", invite.status)
+                                .as_str()
+                        )
+                    ),
+                    ("uses", invite.uses.to_string()),
+                    ("max_uses", invite.max_uses.to_string()),
+                    (
+                        "created",
+                        format!("<t:{}:d>", invite.created_at.and_utc().timestamp())
+                    ),
+                ])
+            )
+        })
+        .collect();
+
+    let page_count = lines.len().div_ceil(INVITES_PER_PAGE);
+    let pages = lines
+        .chunks(INVITES_PER_PAGE)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let params = HashMap::from([
+                ("count", invites.len().to_string()),
+                ("page", (index + 1).to_string()),
+                ("pages", page_count.to_string()),
+            ]);
+
+            CreateEmbed::default()
+                .title(t!(
+                    &locale,
+                    "commands.invites_admin.list.success.title",
+                    params.clone()
+                ))
+                .description(chunk.join("\n"))
+                .color(0x4CACEE)
+                .footer(CreateEmbedFooter::new(t!(
+                    &locale,
+                    "commands.invites_admin.list.success.footer",
+                    params
+                )))
+        })
+        .collect();
+
+    crate::utils::pagination::paginate(ctx, pages, true).await
+}
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum FormatChoice {
+    #[name = "CSV"]
+    Csv,
+    #[name = "JSON"]
+    Json,
+}
+
+impl From<FormatChoice> for ExportFormat {
+    fn from(choice: FormatChoice) -> Self {
+        match choice {
+            FormatChoice::Csv => ExportFormat::Csv,
+            FormatChoice::Json => ExportFormat::Json,
+        }
+    }
+}
+
+/// Export this server's invite history as a file
+#[poise::command(slash_command, guild_only)]
+pub async fn export(
+    ctx: Context<'_>,
+    #[description = "File format (default: CSV)"] format: Option<FormatChoice>,
+    #[description = "Joins on or after this day (YYYY-MM-DD)"] from: Option<String>,
+    #[description = "Joins on or before this day (YYYY-MM-DD)"] to: Option<String>,
+) -> Result<(), Error> {
+    if !check_admin(ctx).await? {
+        return Ok(());
+    }
+    let locale = guild_locale(ctx);
+    let guild_id = ctx.guild_id().unwrap().to_string();
+
+    let Some((from, to)) = parse_date_range(ctx, &locale, from, to).await? else {
+        return Ok(());
+    };
+    let format = format.map(ExportFormat::from).unwrap_or_default();
+
+    let history = crate::utils::db::get_invite_history(&ctx.data().db, &guild_id, from, to).await?;
+    let attachment = CreateAttachment::bytes(
+        format.render(&history)?.into_bytes(),
+        crate::utils::export::file_name(&guild_id, format),
+    );
+
+    let params = HashMap::from([("count", history.len().to_string())]);
+    let embed = CreateEmbed::default()
+        .title(t!(&locale, "commands.invites_admin.export.success.title"))
+        .description(t!(
+            &locale,
+            "commands.invites_admin.export.success.description",
+            params
+        ))
+        .color(0x4CACEE)
+        .footer(CreateEmbedFooter::new(t!(
+            &locale,
+            "commands.invites_admin.export.success.footer"
+        )));
+
+    ctx.send(
+        CreateReply::default()
+            .embed(embed)
+            .attachment(attachment)
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Parses optional `YYYY-MM-DD` bounds, replying with an error and returning
+/// `None` if either is malformed.
+async fn parse_date_range(
+    ctx: Context<'_>,
+    locale: &str,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Option<(Option<NaiveDate>, Option<NaiveDate>)>, Error> {
+    let parse = |date: Option<String>| match date {
+        Some(date) => NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| date),
+        None => Ok(None),
+    };
+
+    match (parse(from), parse(to)) {
+        (Ok(from), Ok(to)) => Ok(Some((from, to))),
+        (Err(date), _) | (_, Err(date)) => {
+            send_embed(
+                ctx,
+                locale,
+                "commands.invites_admin.errors.invalid_date",
+                HashMap::from([("date", date)]),
+                0xFF3333,
+            )
+            .await?;
+            Ok(None)
+        }
+    }
+}
+
+fn guild_locale(ctx: Context<'_>) -> String {
+    let config = ctx.data().config.get();
+    config
+        .get_guild_locale(&ctx.guild_id().unwrap().to_string())
+        .to_string()
+}
+
+/// Replies with an error and returns false unless the author may manage overrides.
+async fn check_admin(ctx: Context<'_>) -> Result<bool, Error> {
+    let guild_id = ctx.guild_id().unwrap().to_string();
+    let config = ctx.data().config.get();
+    let locale = config.get_guild_locale(&guild_id);
+
+    let is_admin = match (
+        ctx.author_member().await,
+        config.guilds.allowed.iter().find(|g| g.id == guild_id),
+    ) {
+        (Some(member), Some(guild_config)) => {
+            crate::utils::permissions::is_admin(&member, guild_config)
+        }
+        _ => false,
+    };
+
+    if !is_admin {
+        send_embed(
+            ctx,
+            locale,
+            "commands.invites_admin.errors.not_admin",
+            HashMap::new(),
+            0xFF3333,
+        )
+        .await?;
+    }
+    Ok(is_admin)
+}
+
+async fn set_override(
+    ctx: Context<'_>,
+    user: &User,
+    limit_override: LimitOverride,
+    expires_in: Option<u32>,
+) -> Result<(), Error> {
+    let (count, days) = match limit_override {
+        LimitOverride::Extra(extra) => (Some(extra), None),
+        LimitOverride::Limit(limit) => (Some(limit.count), Some(limit.days)),
+        LimitOverride::Deny => (None, None),
+    };
+
+    crate::utils::db::set_override(
+        &ctx.data().db,
+        &NewOverride {
+            guild_id: &ctx.guild_id().unwrap().to_string(),
+            user_id: &user.id.to_string(),
+            kind: limit_override.kind(),
+            count,
+            days,
+            created_by: &ctx.author().id.to_string(),
+            duration_days: expires_in,
+        },
+    )
+    .await?;
+    Ok(())
+}
+
+fn expiry_text(locale: &str, expires_in: Option<u32>) -> String {
+    match expires_in {
+        Some(days) => t!(
+            locale,
+            "commands.invites_admin.expiry.days",
+            HashMap::from([("days", days.to_string())])
+        ),
+        None => t!(locale, "commands.invites_admin.expiry.never"),
+    }
+}
+
+async fn send_embed(
+    ctx: Context<'_>,
+    locale: &str,
+    key: &str,
+    params: HashMap<&str, String>,
+    color: u32,
+) -> Result<(), Error> {
+    let embed = CreateEmbed::default()
+        .title(t!(locale, format!("{}.title", key).as_str()))
+        .description(t!(
+            locale,
+            format!("{}.description", key).as_str(),
+            params.clone()
+        ))
+        .color(color)
+        .footer(CreateEmbedFooter::new(t!(
+            locale,
+            format!("{}.footer", key).as_str(),
+            params
+        )));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
@@ -1,22 +1,57 @@
+use crate::utils::db::{LeaderboardMetric, LeaderboardQuery};
 use crate::{t, Context, Error};
 use poise::serenity_prelude::{CreateEmbed, CreateEmbedFooter};
 use poise::CreateReply;
 use std::collections::HashMap;
 
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum Metric {
+    #[name = "Used invites"]
+    Used,
+    #[name = "Retained members"]
+    Retained,
+    #[name = "Invites created"]
+    Created,
+}
+
+impl From<Metric> for LeaderboardMetric {
+    fn from(metric: Metric) -> Self {
+        match metric {
+            Metric::Used => LeaderboardMetric::Used,
+            Metric::Retained => LeaderboardMetric::Retained,
+            Metric::Created => LeaderboardMetric::Created,
+        }
+    }
+}
+
 /// View the invite leaderboard
 #[poise::command(slash_command, guild_only)]
 pub async fn invites_leaderboard(
     ctx: Context<'_>,
     #[description = "Days to look back (default: 30)"] days: Option<i32>,
+    #[description = "What to rank by (default: used invites)"] metric: Option<Metric>,
 ) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap();
     let guild = ctx.guild().unwrap().clone();
-    let locale = ctx.data().config.get_guild_locale(&guild_id.to_string());
+    let config = ctx.data().config.get();
+    let locale = config.get_guild_locale(&guild_id.to_string());
     let days = days.unwrap_or(30);
+    let metric: LeaderboardMetric = metric.unwrap_or(Metric::Used).into();
+    let guild_config = config
+        .guilds
+        .allowed
+        .iter()
+        .find(|g| g.id == guild_id.to_string());
+    let page_size = guild_config
+        .map(|g| g.leaderboard_page_size())
+        .unwrap_or(10);
 
-    let entries =
-        crate::utils::db::get_invite_leaderboard(&ctx.data().db, &guild_id.to_string(), days)
-            .await?;
+    let entries = crate::utils::db::get_invite_leaderboard(
+        &ctx.data().db,
+        &guild_id.to_string(),
+        &LeaderboardQuery::for_guild(guild_config, days, metric),
+    )
+    .await?;
 
     if entries.is_empty() {
         let mut params = HashMap::new();
@@ -43,35 +78,90 @@ pub async fn invites_leaderboard(
         return Ok(());
     }
 
-    let mut description = String::new();
-    for (index, entry) in entries.iter().enumerate() {
-        description.push_str(&format!(
-            "**#{} →** <@{}> [{} invites]\n\n",
-            index + 1,
-            entry.creator_id,
-            entry.invite_count
-        ));
-    }
+    let count_key = format!("commands.invites_leaderboard.counts.{}", metric.as_str());
+    let format_count = |count: i64| {
+        t!(
+            locale,
+            count_key.as_str(),
+            HashMap::from([("count", count.to_string())])
+        )
+    };
+
+    let author_id = ctx.author().id.to_string();
+    let author_rank = entries
+        .iter()
+        .position(|entry| entry.creator_id == author_id);
 
     let mut params = HashMap::new();
     params.insert("guild", guild.name.clone());
-
-    let embed = CreateEmbed::default()
-        .title(t!(
-            locale,
-            "commands.invites_leaderboard.success.title",
-            params
-        ))
-        .description(description)
-        .color(0x4CACEE)
-        .thumbnail(guild.icon_url().unwrap_or_default())
-        .footer(CreateEmbedFooter::new(t!(
+    params.insert("days", days.to_string());
+    params.insert(
+        "metric",
+        t!(
             locale,
-            "commands.invites_leaderboard.success.footer"
-        )));
+            format!("commands.invites_leaderboard.metrics.{}", metric.as_str()).as_str()
+        ),
+    );
+
+    let page_count = entries.len().div_ceil(page_size);
+    let pages = entries
+        .chunks(page_size)
+        .enumerate()
+        .map(|(page, chunk)| {
+            let mut description = format!(
+                "{}\n\n",
+                t!(
+                    locale,
+                    "commands.invites_leaderboard.success.header",
+                    params.clone()
+                )
+            );
+            for (index, entry) in chunk.iter().enumerate() {
+                description.push_str(&format!(
+                    "**#{} →** <@{}> [{}]\n\n",
+                    page * page_size + index + 1,
+                    entry.creator_id,
+                    format_count(entry.invite_count)
+                ));
+            }
+
+            // Keep the caller's own position visible on pages they're not on
+            let on_page = author_rank.is_some_and(|rank| rank / page_size == page);
+            if !on_page {
+                let line = match author_rank {
+                    Some(rank) => t!(
+                        locale,
+                        "commands.invites_leaderboard.success.your_rank",
+                        HashMap::from([
+                            ("rank", (rank + 1).to_string()),
+                            ("count", format_count(entries[rank].invite_count)),
+                        ])
+                    ),
+                    None => t!(locale, "commands.invites_leaderboard.success.not_ranked"),
+                };
+                description.push_str(&line);
+            }
+
+            let mut params = params.clone();
+            params.insert("page", (page + 1).to_string());
+            params.insert("pages", page_count.to_string());
 
-    let reply = CreateReply::default().embed(embed);
-    ctx.send(reply).await?;
+            CreateEmbed::default()
+                .title(t!(
+                    locale,
+                    "commands.invites_leaderboard.success.title",
+                    params.clone()
+                ))
+                .description(description)
+                .color(0x4CACEE)
+                .thumbnail(guild.icon_url().unwrap_or_default())
+                .footer(CreateEmbedFooter::new(t!(
+                    locale,
+                    "commands.invites_leaderboard.success.footer",
+                    params
+                )))
+        })
+        .collect();
 
-    Ok(())
+    crate::utils::pagination::paginate(ctx, pages, false).await
 }
@@ -1,4 +1,7 @@
+pub mod invite_tree;
 pub mod inviter;
 pub mod invites;
+pub mod invites_admin;
 pub mod invites_leaderboard;
 pub mod ping;
+pub mod rewards;
@@ -6,7 +6,8 @@ use poise::CreateReply;
 #[poise::command(slash_command)]
 pub async fn ping(ctx: Context<'_>) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap();
-    let locale = ctx.data().config.get_guild_locale(&guild_id.to_string());
+    let config = ctx.data().config.get();
+    let locale = config.get_guild_locale(&guild_id.to_string());
 
     let embed = CreateEmbed::default()
         .title(t!(locale, "commands.ping.response.title"))
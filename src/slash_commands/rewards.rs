@@ -0,0 +1,101 @@
+use crate::{t, Context, Error};
+use poise::serenity_prelude::{CreateEmbed, CreateEmbedFooter, User};
+use poise::CreateReply;
+use std::collections::HashMap;
+
+/// View progress towards the invite reward tiers
+#[poise::command(slash_command, guild_only)]
+pub async fn rewards(
+    ctx: Context<'_>,
+    #[description = "User to check (default: yourself)"] user: Option<User>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().to_string();
+    let config = ctx.data().config.get();
+    let locale = config.get_guild_locale(&guild_id);
+    let user = user.as_ref().unwrap_or_else(|| ctx.author());
+
+    let guild_config = config
+        .guilds
+        .allowed
+        .iter()
+        .find(|g| g.id == guild_id)
+        .filter(|g| !g.rewards.is_empty());
+    let Some(guild_config) = guild_config else {
+        let embed = CreateEmbed::default()
+            .title(t!(locale, "commands.rewards.errors.no_rewards.title"))
+            .description(t!(locale, "commands.rewards.errors.no_rewards.description"))
+            .color(0xFF3333)
+            .footer(CreateEmbedFooter::new(t!(
+                locale,
+                "commands.rewards.errors.no_rewards.footer"
+            )));
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+        return Ok(());
+    };
+
+    let progress =
+        crate::utils::rewards::progress(ctx.data(), guild_config, &user.id.to_string()).await?;
+
+    let mut lines = Vec::new();
+    for tier in &progress {
+        let mut params = HashMap::new();
+        params.insert("role", format!("<@&{}>", tier.tier.role));
+        params.insert(
+            "count",
+            tier.count.min(i64::from(tier.tier.count)).to_string(),
+        );
+        params.insert("required", tier.tier.count.to_string());
+
+        let mut line = format!(
+            "{} {}",
+            if tier.earned() { "✅" } else { "⬜" },
+            t!(locale, "commands.rewards.success.tier", params)
+        );
+        let mut conditions = Vec::new();
+        if tier.tier.retained {
+            conditions.push(t!(locale, "commands.rewards.success.retained"));
+        }
+        if let Some(days) = tier.tier.days {
+            conditions.push(t!(
+                locale,
+                "commands.rewards.success.window",
+                HashMap::from([("days", days.to_string())])
+            ));
+        }
+        if !conditions.is_empty() {
+            line.push_str(&format!(" · *{}*", conditions.join(", ")));
+        }
+        lines.push(line);
+    }
+
+    let next = match progress.iter().find(|tier| !tier.earned()) {
+        Some(next) => t!(
+            locale,
+            "commands.rewards.success.next",
+            HashMap::from([
+                ("remaining", next.remaining().to_string()),
+                ("role", format!("<@&{}>", next.tier.role)),
+            ])
+        ),
+        None => t!(locale, "commands.rewards.success.all_earned"),
+    };
+
+    let embed = CreateEmbed::default()
+        .title(t!(
+            locale,
+            "commands.rewards.success.title",
+            HashMap::from([("name", user.name.clone())])
+        ))
+        .description(format!("{}\n\n{}", lines.join("\n"), next))
+        .color(0x4CACEE)
+        .thumbnail(user.face())
+        .footer(CreateEmbedFooter::new(t!(
+            locale,
+            "commands.rewards.success.footer"
+        )));
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
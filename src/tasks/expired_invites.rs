@@ -0,0 +1,45 @@
+use crate::Data;
+use log::{debug, warn};
+use poise::serenity_prelude::Http;
+use std::sync::Arc;
+use std::time::Duration;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Periodically deletes Discord invites left behind by expired bot links.
+pub fn spawn(http: Arc<Http>, data: Data) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            ticker.tick().await;
+            sweep(&http, &data).await;
+        }
+    });
+}
+
+async fn sweep(http: &Http, data: &Data) {
+    let expired = match crate::utils::db::get_expired_invites(&data.db).await {
+        Ok(expired) => expired,
+        Err(e) => {
+            warn!("Failed to fetch expired invites: {}", e);
+            return;
+        }
+    };
+
+    for invite in expired {
+        debug!("Sweeping expired invite {}.", invite.id);
+        data.invites
+            .remove(&invite.guild_id, &invite.discord_invite_code)
+            .await;
+        // Discord may have expired the code on its own already
+        let _ = http
+            .delete_invite(&invite.discord_invite_code, Some("Invite link expired"))
+            .await;
+        if let Err(e) = crate::utils::db::mark_invite_swept(&data.db, &invite.id).await {
+            warn!(
+                "Failed to mark expired invite {} as swept: {}",
+                invite.id, e
+            );
+        }
+    }
+}
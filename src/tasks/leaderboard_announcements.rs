@@ -0,0 +1,171 @@
+use crate::t;
+use crate::utils::config::{AllowedGuild, LeaderboardAnnouncement};
+use crate::utils::db::{InviteLeaderboardEntry, LeaderboardMetric, LeaderboardQuery};
+use crate::Data;
+use log::warn;
+use poise::serenity_prelude::{
+    ChannelId, CreateEmbed, CreateEmbedFooter, CreateMessage, GuildId, Http, Timestamp,
+};
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+use std::sync::Arc;
+use std::time::Duration;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Posts each guild's leaderboard on its configured weekly schedule.
+pub fn spawn(http: Arc<Http>, data: Data) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let config = data.config.get();
+            for guild_config in &config.guilds.allowed {
+                if let Some(announcement) = &guild_config.leaderboard_announcement {
+                    check(&http, &data, guild_config, announcement).await;
+                }
+            }
+        }
+    });
+}
+
+async fn check(
+    http: &Http,
+    data: &Data,
+    guild_config: &AllowedGuild,
+    announcement: &LeaderboardAnnouncement,
+) {
+    let now = chrono::Utc::now().naive_utc();
+    let last_posted =
+        match crate::utils::db::get_last_announcement(&data.db, &guild_config.id).await {
+            Ok(last_posted) => last_posted,
+            Err(e) => {
+                warn!(
+                    "Failed to read last announcement of guild {}: {}",
+                    guild_config.id, e
+                );
+                return;
+            }
+        };
+    if !announcement.is_due(now, last_posted) {
+        return;
+    }
+
+    if let Err(e) = announce(http, data, guild_config, announcement).await {
+        warn!(
+            "Failed to announce leaderboard of guild {}: {}",
+            guild_config.id, e
+        );
+        return;
+    }
+    if let Err(e) = crate::utils::db::set_last_announcement(&data.db, &guild_config.id, now).await {
+        warn!(
+            "Failed to record announcement of guild {}: {}",
+            guild_config.id, e
+        );
+    }
+}
+
+async fn announce(
+    http: &Http,
+    data: &Data,
+    guild_config: &AllowedGuild,
+    announcement: &LeaderboardAnnouncement,
+) -> Result<(), crate::Error> {
+    let config = data.config.get();
+    let locale = config.get_guild_locale(&guild_config.id);
+    let channel_id = ChannelId::from(announcement.channel.parse::<NonZeroU64>()?);
+    let days = announcement.days() as i32;
+
+    let mut query = LeaderboardQuery::for_guild(Some(guild_config), days, LeaderboardMetric::Used);
+    let current =
+        crate::utils::db::get_invite_leaderboard(&data.db, &guild_config.id, &query).await?;
+    query.offset_days = days;
+    let previous =
+        crate::utils::db::get_invite_leaderboard(&data.db, &guild_config.id, &query).await?;
+
+    let guild = GuildId::from(guild_config.id.parse::<NonZeroU64>()?)
+        .to_partial_guild(http)
+        .await
+        .ok();
+    let mut params = HashMap::new();
+    params.insert(
+        "guild",
+        guild
+            .as_ref()
+            .map(|g| g.name.clone())
+            .unwrap_or_else(|| guild_config.name.clone()),
+    );
+    params.insert("days", days.to_string());
+
+    let description = if current.is_empty() {
+        t!(locale, "announcement.leaderboard.empty", params.clone())
+    } else {
+        let mut description = format!(
+            "{}\n\n",
+            t!(locale, "announcement.leaderboard.header", params.clone())
+        );
+        for (rank, entry) in current
+            .iter()
+            .take(guild_config.leaderboard_page_size())
+            .enumerate()
+        {
+            description.push_str(&format!(
+                "**#{} →** <@{}> [{}] {}\n\n",
+                rank + 1,
+                entry.creator_id,
+                t!(
+                    locale,
+                    "commands.invites_leaderboard.counts.used",
+                    HashMap::from([("count", entry.invite_count.to_string())])
+                ),
+                change(locale, entry, &previous)
+            ));
+        }
+        description
+    };
+
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "announcement.leaderboard.title", params.clone()))
+        .description(description)
+        .color(0x4CACEE)
+        .thumbnail(guild.and_then(|g| g.icon_url()).unwrap_or_default())
+        .footer(CreateEmbedFooter::new(t!(
+            locale,
+            "announcement.leaderboard.footer",
+            params
+        )))
+        .timestamp(Timestamp::now());
+
+    channel_id
+        .send_message(http, CreateMessage::new().embed(embed))
+        .await?;
+    Ok(())
+}
+
+/// How an inviter's count moved since the previous period.
+fn change(
+    locale: &str,
+    entry: &InviteLeaderboardEntry,
+    previous: &[InviteLeaderboardEntry],
+) -> String {
+    let before = previous
+        .iter()
+        .find(|p| p.creator_id == entry.creator_id)
+        .map(|p| p.invite_count);
+
+    match before {
+        None => t!(locale, "announcement.leaderboard.change.new"),
+        Some(before) if entry.invite_count > before => t!(
+            locale,
+            "announcement.leaderboard.change.up",
+            HashMap::from([("delta", (entry.invite_count - before).to_string())])
+        ),
+        Some(before) if entry.invite_count < before => t!(
+            locale,
+            "announcement.leaderboard.change.down",
+            HashMap::from([("delta", (before - entry.invite_count).to_string())])
+        ),
+        Some(_) => t!(locale, "announcement.leaderboard.change.same"),
+    }
+}
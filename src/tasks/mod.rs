@@ -0,0 +1,3 @@
+pub mod expired_invites;
+pub mod leaderboard_announcements;
+pub mod reward_sweep;
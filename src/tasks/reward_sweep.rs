@@ -0,0 +1,48 @@
+use crate::Data;
+use log::{debug, warn};
+use poise::serenity_prelude::Http;
+use std::sync::Arc;
+use std::time::Duration;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Periodically re-checks reward roles of tiers that only count recent invitees,
+/// since those can be lost without anyone joining or leaving.
+pub fn spawn(http: Arc<Http>, data: Data) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            ticker.tick().await;
+            sweep(&http, &data).await;
+        }
+    });
+}
+
+async fn sweep(http: &Http, data: &Data) {
+    let config = data.config.get();
+    for guild_config in &config.guilds.allowed {
+        if !guild_config.rewards.iter().any(|tier| tier.days.is_some()) {
+            continue;
+        }
+        let inviters = match crate::utils::db::get_reward_inviters(&data.db, &guild_config.id).await
+        {
+            Ok(inviters) => inviters,
+            Err(e) => {
+                warn!(
+                    "Failed to fetch inviters of guild {} for rewards: {}",
+                    guild_config.id, e
+                );
+                continue;
+            }
+        };
+
+        debug!(
+            "Re-checking reward roles of {} inviters in guild {}.",
+            inviters.len(),
+            guild_config.id
+        );
+        for inviter in inviters {
+            crate::utils::rewards::evaluate(http, data, guild_config, &inviter).await;
+        }
+    }
+}
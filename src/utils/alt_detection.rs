@@ -0,0 +1,141 @@
+use crate::utils::config::AltDetection;
+
+/// A hint that an invitee may be an alt account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// The account is younger than `min_account_age_days`.
+    NewAccount,
+    /// The account never set an avatar.
+    DefaultAvatar,
+    /// The user joined this guild before.
+    Rejoin,
+}
+
+impl Signal {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Signal::NewAccount => "new_account",
+            Signal::DefaultAvatar => "default_avatar",
+            Signal::Rejoin => "rejoin",
+        }
+    }
+
+    fn weight(&self) -> u32 {
+        match self {
+            Signal::NewAccount => 2,
+            Signal::DefaultAvatar | Signal::Rejoin => 1,
+        }
+    }
+}
+
+/// What is known about an invitee when their join is attributed.
+#[derive(Debug, Clone, Copy)]
+pub struct Invitee {
+    pub account_age_days: i64,
+    pub default_avatar: bool,
+    pub rejoin: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assessment {
+    pub signals: Vec<Signal>,
+    pub flagged: bool,
+}
+
+impl Assessment {
+    /// Comma-separated signals, as stored on the invite row.
+    pub fn flags(&self) -> String {
+        self.signals
+            .iter()
+            .map(Signal::as_str)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+pub fn assess(invitee: &Invitee, settings: &AltDetection) -> Assessment {
+    let min_age = settings.min_account_age_days.unwrap_or(7) as i64;
+    let signals: Vec<Signal> = [
+        (invitee.account_age_days < min_age).then_some(Signal::NewAccount),
+        invitee.default_avatar.then_some(Signal::DefaultAvatar),
+        invitee.rejoin.then_some(Signal::Rejoin),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let score: u32 = signals.iter().map(Signal::weight).sum();
+    Assessment {
+        flagged: score >= settings.threshold.unwrap_or(2),
+        signals,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assess() {
+        let settings = AltDetection::default();
+        let invitee = Invitee {
+            account_age_days: 400,
+            default_avatar: false,
+            rejoin: false,
+        };
+
+        let assessment = assess(&invitee, &settings);
+        assert!(assessment.signals.is_empty());
+        assert!(!assessment.flagged);
+
+        // A default avatar alone is not enough
+        let assessment = assess(
+            &Invitee {
+                default_avatar: true,
+                ..invitee
+            },
+            &settings,
+        );
+        assert!(!assessment.flagged);
+
+        let assessment = assess(
+            &Invitee {
+                account_age_days: 1,
+                ..invitee
+            },
+            &settings,
+        );
+        assert!(assessment.flagged);
+        assert_eq!(assessment.flags(), "new_account");
+
+        let assessment = assess(
+            &Invitee {
+                default_avatar: true,
+                rejoin: true,
+                ..invitee
+            },
+            &settings,
+        );
+        assert!(assessment.flagged);
+        assert_eq!(assessment.flags(), "default_avatar,rejoin");
+    }
+
+    #[test]
+    fn test_assess_settings() {
+        let settings = AltDetection {
+            min_account_age_days: Some(30),
+            threshold: Some(3),
+            ..Default::default()
+        };
+        let assessment = assess(
+            &Invitee {
+                account_age_days: 20,
+                default_avatar: false,
+                rejoin: false,
+            },
+            &settings,
+        );
+        assert_eq!(assessment.signals, vec![Signal::NewAccount]);
+        assert!(!assessment.flagged);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::t;
+use crate::utils::config::AllowedGuild;
+use poise::serenity_prelude::{
+    ButtonStyle, ChannelId, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter,
+    CreateMessage, Http, User,
+};
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+
+/// Prefix of the custom id of the Approve and Deny buttons on a request card.
+const CUSTOM_ID_PREFIX: &str = "invite-approval";
+
+pub fn custom_id(approve: bool, invite_id: &str) -> String {
+    let action = if approve { "approve" } else { "deny" };
+    format!("{}:{}:{}", CUSTOM_ID_PREFIX, action, invite_id)
+}
+
+/// Reads a button's custom id back into whether it approves, and the invite id.
+/// `None` for components that aren't approval buttons.
+pub fn parse_custom_id(custom_id: &str) -> Option<(bool, &str)> {
+    let rest = custom_id
+        .strip_prefix(CUSTOM_ID_PREFIX)?
+        .strip_prefix(':')?;
+    let (action, invite_id) = rest.split_once(':')?;
+    match action {
+        "approve" => Some((true, invite_id)),
+        "deny" => Some((false, invite_id)),
+        _ => None,
+    }
+}
+
+/// Posts a card with Approve and Deny buttons to the guild's review channel.
+pub async fn post_request(
+    http: &Http,
+    guild_config: &AllowedGuild,
+    locale: &str,
+    requester: &User,
+    invite_id: &str,
+    note: Option<&str>,
+    intended_for: Option<&str>,
+) -> Result<(), crate::Error> {
+    let channel_id = ChannelId::from(
+        guild_config
+            .review_channel()
+            .ok_or("no review channel configured")?
+            .parse::<NonZeroU64>()?,
+    );
+
+    let mut params = HashMap::new();
+    params.insert("user", format!("<@{}>", requester.id));
+    params.insert("name", requester.name.clone());
+    params.insert("id", requester.id.to_string());
+
+    let mut embed = CreateEmbed::default()
+        .title(t!(locale, "approval.request.title"))
+        .description(t!(locale, "approval.request.description", params.clone()))
+        .field(
+            t!(locale, "approval.request.note"),
+            note.map(str::to_string)
+                .unwrap_or_else(|| t!(locale, "approval.request.no_note")),
+            false,
+        );
+    if let Some(intended_for) = intended_for {
+        embed = embed.field(
+            t!(locale, "approval.request.intended_for"),
+            crate::utils::reservation::display(intended_for),
+            false,
+        );
+    }
+    let embed = embed
+        .field(
+            t!(locale, "approval.request.invite_id"),
+            format!("`{}`", invite_id),
+            false,
+        )
+        .color(0xFFA500)
+        .thumbnail(requester.face())
+        .footer(CreateEmbedFooter::new(t!(
+            locale,
+            "approval.request.footer",
+            params
+        )));
+
+    let buttons = CreateActionRow::Buttons(vec![
+        CreateButton::new(custom_id(true, invite_id))
+            .label(t!(locale, "approval.buttons.approve"))
+            .style(ButtonStyle::Success),
+        CreateButton::new(custom_id(false, invite_id))
+            .label(t!(locale, "approval.buttons.deny"))
+            .style(ButtonStyle::Danger),
+    ]);
+
+    channel_id
+        .send_message(
+            http,
+            CreateMessage::new().embed(embed).components(vec![buttons]),
+        )
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_id() {
+        let invite_id = "0b5c7a4e-1f7e-4a8e-9a57-4e3f4c3b2a10";
+
+        assert_eq!(
+            parse_custom_id(&custom_id(true, invite_id)),
+            Some((true, invite_id))
+        );
+        assert_eq!(
+            parse_custom_id(&custom_id(false, invite_id)),
+            Some((false, invite_id))
+        );
+        // 其他元件（例如分頁按鈕）不處理
+        assert_eq!(parse_custom_id("123456next"), None);
+        assert_eq!(parse_custom_id("invite-approval:maybe:abc"), None);
+    }
+}
@@ -1,6 +1,13 @@
 use crate::i18n::AVAILABLE_LOCALES;
+use chrono::{Datelike, NaiveDateTime, Weekday};
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// Placeholder shown instead of secrets in API responses.
+pub const REDACTED: &str = "********";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -32,6 +39,9 @@ pub struct DatabaseConfig {
 pub struct ServerConfig {
     pub external_url: String,
     pub bind: String,
+    /// Bearer token required by the admin HTTP endpoints. They stay disabled when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,7 +49,7 @@ pub struct GuildConfig {
     pub allowed: Vec<AllowedGuild>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AllowedGuild {
     pub id: String,
     pub name: String,
@@ -48,31 +58,395 @@ pub struct AllowedGuild {
     pub min_member_age: Option<u32>,
     pub locale: Option<String>,
     pub allowed_roles: Vec<AllowedRole>,
+    /// Uses per Discord invite (default 1). Above 1 makes multi-use campaign links.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<u8>,
+    /// Seconds a bot invite link stays redeemable, from its approval if it needs one.
+    /// Links never expire when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_ttl: Option<u32>,
+    /// Unused, unexpired links a member may hold at once. Unlimited when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_outstanding: Option<u32>,
+    /// How to combine the limits of several matching roles. Defaults to `max`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_policy: Option<LimitPolicy>,
+    /// Which invitees count on leaderboards. Invite limits always count every
+    /// invitee. Defaults to `all`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count_mode: Option<CountMode>,
+    /// Days an invitee must stay before leaving for the `retained` mode. Defaults to 7.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_days: Option<u32>,
+    /// Roles allowed to manage every invite, on top of Manage Server.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub admin_roles: Vec<String>,
+    /// Channel for moderation reports. Nothing is reported when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mod_log_channel: Option<String>,
+    /// Scoring of invitees for alt accounts. Disabled when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt_detection: Option<AltDetection>,
+    /// Inviters per leaderboard page, 1 to 25. Defaults to 10.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaderboard_page_size: Option<u32>,
+    /// Whether invites imported from another tracker count on the leaderboard. Defaults to false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaderboard_include_imported: Option<bool>,
+    /// Whether links need a moderator's approval before they can be redeemed. Defaults to false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_approval: Option<bool>,
+    /// Channel for approval requests. Defaults to the mod log channel.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_channel: Option<String>,
+    /// What happens when someone other than the intended invitee joins through a
+    /// reserved link. Defaults to reporting it to the mod log.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reservation_mismatch: Option<MismatchAction>,
+    /// Role given to members held back by the `quarantine` mismatch action.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarantine_role: Option<String>,
+    /// Posts the leaderboard to a channel on a weekly schedule. Off when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaderboard_announcement: Option<LeaderboardAnnouncement>,
+    /// What happens to an inviter when someone they invited is banned. Nothing when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ban_policy: Option<BanPolicy>,
+    /// Greets invitees and tells their inviter. Off when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub welcome: Option<Welcome>,
+    /// Roles inviters earn at invite milestones. The bot gives and takes these
+    /// roles as counts change, so they shouldn't be handed out by hand.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rewards: Vec<RewardTier>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl AllowedGuild {
+    pub fn invite_max_uses(&self) -> u8 {
+        self.max_uses.unwrap_or(1).clamp(1, 100)
+    }
+
+    pub fn review_channel(&self) -> Option<&str> {
+        self.review_channel
+            .as_deref()
+            .or(self.mod_log_channel.as_deref())
+    }
+
+    pub fn leaderboard_page_size(&self) -> usize {
+        self.leaderboard_page_size.unwrap_or(10).clamp(1, 25) as usize
+    }
+
+    pub fn retention(&self) -> Retention {
+        match self.count_mode.unwrap_or_default() {
+            CountMode::All => Retention::All,
+            CountMode::Present => Retention::Present,
+            CountMode::Retained => Retention::MinDays(self.retention_days.unwrap_or(7)),
+        }
+    }
+
+    /// Reward tiers from the lowest threshold up.
+    pub fn reward_tiers(&self) -> Vec<&RewardTier> {
+        let mut tiers: Vec<&RewardTier> = self.rewards.iter().collect();
+        tiers.sort_by_key(|tier| tier.count);
+        tiers
+    }
+
+    /// Which invitees count towards a reward tier.
+    pub fn reward_retention(&self, tier: &RewardTier) -> Retention {
+        if tier.retained {
+            Retention::MinDays(self.retention_days.unwrap_or(7))
+        } else {
+            Retention::All
+        }
+    }
+
+    /// Roles an invitee of a member holding `role_ids` gets on joining, from
+    /// each allowed role they hold, without duplicates.
+    pub fn grant_roles_on_join(&self, role_ids: &[String]) -> Vec<&str> {
+        let mut grant_roles: Vec<&str> = Vec::new();
+        for role in self
+            .allowed_roles
+            .iter()
+            .filter(|role| role_ids.contains(&role.id))
+        {
+            for grant_role in &role.grant_roles_on_join {
+                if !grant_roles.contains(&grant_role.as_str()) {
+                    grant_roles.push(grant_role);
+                }
+            }
+        }
+        grant_roles
+    }
+
+    /// Resolves the invite limit for a member holding `role_ids`, or `None` if
+    /// none of them is an allowed role.
+    pub fn effective_limit(&self, role_ids: &[String]) -> Option<EffectiveLimit<'_>> {
+        let matching: Vec<&AllowedRole> = self
+            .allowed_roles
+            .iter()
+            .filter(|role| role_ids.contains(&role.id))
+            .collect();
+        if matching.is_empty() {
+            return None;
+        }
+
+        let policy = self.limit_policy.unwrap_or_default();
+        let (limit, roles) = match policy {
+            LimitPolicy::Max => {
+                let role = matching
+                    .iter()
+                    .copied()
+                    .reduce(|best, role| {
+                        let (a, b) = (&best.invite_limit, &role.invite_limit);
+                        if b.rate() > a.rate() || (b.rate() == a.rate() && b.count > a.count) {
+                            role
+                        } else {
+                            best
+                        }
+                    })
+                    .unwrap();
+                (role.invite_limit, vec![role])
+            }
+            LimitPolicy::Sum => {
+                let limit = InviteLimit {
+                    count: matching.iter().map(|role| role.invite_limit.count).sum(),
+                    days: matching
+                        .iter()
+                        .map(|role| role.invite_limit.days)
+                        .max()
+                        .unwrap(),
+                };
+                (limit, matching)
+            }
+            LimitPolicy::Priority => {
+                // max_by_key keeps the last maximum, so reverse to prefer config order on ties
+                let role = matching
+                    .iter()
+                    .rev()
+                    .copied()
+                    .max_by_key(|role| role.priority.unwrap_or(0))
+                    .unwrap();
+                (role.invite_limit, vec![role])
+            }
+        };
+
+        Some(EffectiveLimit {
+            limit,
+            roles,
+            policy,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AllowedRole {
     pub id: String,
     pub invite_limit: InviteLimit,
+    /// Used by the `priority` limit policy; higher wins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+    /// Roles given to members who join through an invite from a holder of this role.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grant_roles_on_join: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct InviteLimit {
     pub count: i32,
     pub days: i32,
 }
 
+impl InviteLimit {
+    /// Invites per day, used to compare limits with different windows.
+    fn rate(&self) -> f64 {
+        self.count as f64 / self.days.max(1) as f64
+    }
+}
+
+/// How the limit is chosen when a member holds several allowed roles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitPolicy {
+    /// The most generous single role, by invites per day.
+    #[default]
+    Max,
+    /// The counts of all matching roles added up, over the longest window.
+    Sum,
+    /// The role with the highest `priority`.
+    Priority,
+}
+
+/// Which invitees count, as configured per guild.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CountMode {
+    /// Every recorded join, even if the invitee left again.
+    #[default]
+    All,
+    /// Only invitees who are still members.
+    Present,
+    /// Invitees who are still members or stayed at least `retention_days`.
+    Retained,
+}
+
+/// Resolved form of [`CountMode`], passed to the invite count queries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Retention {
+    #[default]
+    All,
+    Present,
+    MinDays(u32),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AltDetection {
+    /// Accounts younger than this are suspicious. Defaults to 7 days.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_account_age_days: Option<u32>,
+    /// Score at which a join is flagged. Defaults to 2.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<u32>,
+    /// Leave flagged joins out of leaderboard counts.
+    #[serde(default)]
+    pub exclude_from_leaderboard: bool,
+    /// Flagged invitees after which the inviter is called out in the mod log. Defaults to 3.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat_threshold: Option<u32>,
+}
+
+/// Consequences for an inviter whose invitee gets banned. Any combination may be set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BanPolicy {
+    /// Report the ban and its inviter to the mod log.
+    #[serde(default)]
+    pub notify: bool,
+    /// Invites taken off the inviter's quota, until an admin resets it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_penalty: Option<u32>,
+    /// Days the inviter may not create invites.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suspend_days: Option<u32>,
+    /// Remove the inviter's allowed roles.
+    #[serde(default)]
+    pub strip_roles: bool,
+}
+
+/// A role earned by bringing in `count` invitees.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RewardTier {
+    pub role: String,
+    pub count: u32,
+    /// Only count invitees who are still members or stayed `retention_days`.
+    #[serde(default)]
+    pub retained: bool,
+    /// Only count invitees who joined in the last this many days. All time when unset.
+    /// Roles of windowed tiers are re-checked hourly as old invitees age out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days: Option<u32>,
+}
+
+/// Messages sent when a member joins through a bot invite. The templates take
+/// `{user}`, `{inviter}`, `{guild}`, `{count}` and `{ordinal}`, the inviter's
+/// total invitees so far.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Welcome {
+    /// Channel the welcome message is posted to. Nothing is posted when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    /// Replaces the locale's welcome message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// DM the inviter that their invite was used.
+    #[serde(default)]
+    pub dm_inviter: bool,
+    /// Replaces the locale's DM to the inviter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dm_message: Option<String>,
+}
+
+/// Action against a member who joined through a link reserved for someone else.
+/// Every action is reported to the mod log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchAction {
+    /// Only report the join.
+    #[default]
+    Flag,
+    Kick,
+    /// Give the member the `quarantine_role`.
+    Quarantine,
+}
+
+/// A weekly leaderboard post, with each inviter's change since the period before.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LeaderboardAnnouncement {
+    pub channel: String,
+    /// Day of the week to post on. Defaults to Monday.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekday: Option<Weekday>,
+    /// Hour of the day to post at, in UTC. Defaults to 0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hour: Option<u32>,
+    /// Days the leaderboard looks back. Defaults to 7.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days: Option<u32>,
+}
+
+impl LeaderboardAnnouncement {
+    pub fn days(&self) -> u32 {
+        self.days.unwrap_or(7).max(1)
+    }
+
+    /// The most recent scheduled time at or before `now`, in UTC.
+    pub fn latest_slot(&self, now: NaiveDateTime) -> NaiveDateTime {
+        let weekday = self.weekday.unwrap_or(Weekday::Mon);
+        let hour = self.hour.unwrap_or(0).min(23);
+        let days_back =
+            (now.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+        let slot = (now.date() - chrono::Duration::days(days_back as i64))
+            .and_hms_opt(hour, 0, 0)
+            .unwrap();
+        if slot > now {
+            slot - chrono::Duration::days(7)
+        } else {
+            slot
+        }
+    }
+
+    /// Whether a post is owed, given when the last one went out. Without a previous
+    /// post only a slot from the last day counts, so a schedule set up midweek waits
+    /// for its first slot instead of posting right away.
+    pub fn is_due(&self, now: NaiveDateTime, last_posted: Option<NaiveDateTime>) -> bool {
+        let slot = self.latest_slot(now);
+        match last_posted {
+            Some(last_posted) => last_posted < slot,
+            None => now - slot < chrono::Duration::days(1),
+        }
+    }
+}
+
+/// The limit that applies to a member, and the roles it came from.
+#[derive(Debug, Clone)]
+pub struct EffectiveLimit<'a> {
+    pub limit: InviteLimit,
+    pub roles: Vec<&'a AllowedRole>,
+    pub policy: LimitPolicy,
+}
+
 impl Config {
     pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let content = fs::read_to_string(path)?;
         let config: Config = serde_yaml::from_str(&content)?;
+        config.validate()?;
+        Ok(config)
+    }
 
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // Validate locales
-        if !AVAILABLE_LOCALES.contains(&config.i18n.default_locale.as_str()) {
-            return Err(format!("Unsupported locale: {}", config.i18n.default_locale).into());
+        if !AVAILABLE_LOCALES.contains(&self.i18n.default_locale.as_str()) {
+            return Err(format!("Unsupported locale: {}", self.i18n.default_locale).into());
         }
-
-        Ok(config)
+        Ok(())
     }
 
     pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -81,6 +455,26 @@ impl Config {
         Ok(())
     }
 
+    /// Returns a copy with secrets masked, safe to hand out over HTTP.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        config.bot.token = REDACTED.to_string();
+        if config.server.admin_token.is_some() {
+            config.server.admin_token = Some(REDACTED.to_string());
+        }
+        config
+    }
+
+    /// Puts back any secret that is still masked, taking it from `current`.
+    pub fn restore_secrets(&mut self, current: &Config) {
+        if self.bot.token == REDACTED {
+            self.bot.token = current.bot.token.clone();
+        }
+        if self.server.admin_token.as_deref() == Some(REDACTED) {
+            self.server.admin_token = current.server.admin_token.clone();
+        }
+    }
+
     pub fn get_guild_locale(&self, guild_id: &str) -> &str {
         self.guilds
             .allowed
@@ -91,6 +485,59 @@ impl Config {
     }
 }
 
+/// Config handle shared by the bot and the HTTP server.
+///
+/// Readers take a cheap snapshot with [`SharedConfig::get`]; a reload swaps the
+/// whole config at once so nobody observes a half-updated state. The bot token,
+/// database URI and bind address are only read at startup.
+#[derive(Debug, Clone)]
+pub struct SharedConfig(Arc<RwLock<Arc<Config>>>);
+
+impl SharedConfig {
+    pub fn new(config: Config) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(config))))
+    }
+
+    pub fn get(&self) -> Arc<Config> {
+        self.0.read().unwrap().clone()
+    }
+
+    pub fn replace(&self, config: Config) {
+        *self.0.write().unwrap() = Arc::new(config);
+    }
+
+    pub fn reload(&self, path: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.replace(Config::load(path)?);
+        Ok(())
+    }
+
+    /// Polls `path` and reloads the config whenever the file changes on disk.
+    pub fn watch(&self, path: String, interval: Duration) {
+        let shared = self.clone();
+        tokio::spawn(async move {
+            let mut last_modified = modified_at(&path);
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let modified = modified_at(&path);
+                if modified == last_modified {
+                    continue;
+                }
+                last_modified = modified;
+
+                match shared.reload(&path) {
+                    Ok(()) => info!("Reloaded config from {}", path),
+                    Err(e) => warn!("Failed to reload config from {}: {}", path, e),
+                }
+            }
+        });
+    }
+}
+
+fn modified_at(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,6 +558,7 @@ mod tests {
             server: ServerConfig {
                 external_url: "http://localhost:8080".to_string(),
                 bind: "127.0.0.1:8080".to_string(),
+                admin_token: None,
             },
             i18n: I18nConfig {
                 default_locale: "en".to_string(),
@@ -171,7 +619,7 @@ mod tests {
             max_age: None,
             min_member_age: None,
             locale: Some("zh-TW".to_string()),
-            allowed_roles: vec![],
+            ..Default::default()
         });
 
         // 測試指定公會的語言設定
@@ -200,7 +648,9 @@ mod tests {
             allowed_roles: vec![AllowedRole {
                 id: "789".to_string(),
                 invite_limit: InviteLimit { count: 5, days: 7 },
+                ..Default::default()
             }],
+            ..Default::default()
         });
 
         let guild = &config.guilds.allowed[0];
@@ -208,12 +658,222 @@ mod tests {
         // 測試自定義過期時間
         assert_eq!(guild.max_age.unwrap(), 7200);
 
+        // 未設定時邀請為單次使用
+        assert_eq!(guild.invite_max_uses(), 1);
+
         // 測試角色邀請限制
         let role = &guild.allowed_roles[0];
         assert_eq!(role.invite_limit.count, 5);
         assert_eq!(role.invite_limit.days, 7);
     }
 
+    #[test]
+    fn test_effective_limit() {
+        let role = |id: &str, count, days, priority| AllowedRole {
+            id: id.to_string(),
+            invite_limit: InviteLimit { count, days },
+            priority,
+            ..Default::default()
+        };
+        let mut guild = AllowedGuild {
+            allowed_roles: vec![
+                role("manager", 2, 30, Some(10)),
+                role("admin", 10, 30, None),
+                role("weekly", 3, 7, None),
+            ],
+            ..Default::default()
+        };
+        let held = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+        // 沒有符合的角色
+        assert!(guild.effective_limit(&held(&["other"])).is_none());
+
+        // 預設取最寬鬆的限制，與角色順序無關
+        let effective = guild.effective_limit(&held(&["manager", "admin"])).unwrap();
+        assert_eq!(effective.policy, LimitPolicy::Max);
+        assert_eq!(
+            effective.limit,
+            InviteLimit {
+                count: 10,
+                days: 30
+            }
+        );
+        assert_eq!(effective.roles[0].id, "admin");
+
+        // 以每日額度比較不同的天數
+        let effective = guild.effective_limit(&held(&["admin", "weekly"])).unwrap();
+        assert_eq!(effective.limit, InviteLimit { count: 3, days: 7 });
+
+        guild.limit_policy = Some(LimitPolicy::Sum);
+        let effective = guild
+            .effective_limit(&held(&["manager", "admin", "weekly"]))
+            .unwrap();
+        assert_eq!(
+            effective.limit,
+            InviteLimit {
+                count: 15,
+                days: 30
+            }
+        );
+        assert_eq!(effective.roles.len(), 3);
+
+        guild.limit_policy = Some(LimitPolicy::Priority);
+        let effective = guild.effective_limit(&held(&["manager", "admin"])).unwrap();
+        assert_eq!(effective.limit, InviteLimit { count: 2, days: 30 });
+
+        // 優先度相同時依設定順序
+        let effective = guild.effective_limit(&held(&["admin", "weekly"])).unwrap();
+        assert_eq!(effective.roles[0].id, "admin");
+    }
+
+    #[test]
+    fn test_leaderboard_announcement_schedule() {
+        let at = |value: &str| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap();
+        let announcement: LeaderboardAnnouncement =
+            serde_yaml::from_str("channel: \"1\"\nweekday: friday\nhour: 18").unwrap();
+
+        // 2025-01-01 是星期三，上一個時段是前一週的星期五
+        assert_eq!(
+            announcement.latest_slot(at("2025-01-01 12:00")),
+            at("2024-12-27 18:00")
+        );
+        assert_eq!(
+            announcement.latest_slot(at("2025-01-03 18:00")),
+            at("2025-01-03 18:00")
+        );
+        assert_eq!(
+            announcement.latest_slot(at("2025-01-03 17:59")),
+            at("2024-12-27 18:00")
+        );
+
+        // 已在本時段發布過就不重複發布
+        let now = at("2025-01-03 18:10");
+        assert!(announcement.is_due(now, Some(at("2024-12-27 18:10"))));
+        assert!(!announcement.is_due(now, Some(at("2025-01-03 18:00"))));
+
+        // 沒有紀錄時只補發一天內的時段
+        assert!(announcement.is_due(now, None));
+        assert!(!announcement.is_due(at("2025-01-05 12:00"), None));
+    }
+
+    #[test]
+    fn test_grant_roles_on_join() {
+        let role = |id: &str, grant_roles: &[&str]| AllowedRole {
+            id: id.to_string(),
+            grant_roles_on_join: grant_roles.iter().map(|id| id.to_string()).collect(),
+            ..Default::default()
+        };
+        let guild = AllowedGuild {
+            allowed_roles: vec![
+                role("staff", &["staff_guest", "guest"]),
+                role("member", &["guest"]),
+                role("plain", &[]),
+            ],
+            ..Default::default()
+        };
+        let held = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(guild.grant_roles_on_join(&held(&["member"])), ["guest"]);
+        // 多個角色給予相同身分組時只算一次
+        assert_eq!(
+            guild.grant_roles_on_join(&held(&["member", "staff"])),
+            ["staff_guest", "guest"]
+        );
+        assert!(guild.grant_roles_on_join(&held(&["plain"])).is_empty());
+    }
+
+    #[test]
+    fn test_reward_tiers() {
+        let tier = |role: &str, count, retained| RewardTier {
+            role: role.to_string(),
+            count,
+            retained,
+            days: None,
+        };
+        let mut guild = AllowedGuild {
+            rewards: vec![tier("vip", 20, true), tier("badge", 5, false)],
+            ..Default::default()
+        };
+
+        // 依門檻由低到高排列
+        let tiers = guild.reward_tiers();
+        assert_eq!(tiers[0].role, "badge");
+        assert_eq!(tiers[1].role, "vip");
+
+        assert_eq!(guild.reward_retention(tiers[0]), Retention::All);
+        assert_eq!(guild.reward_retention(tiers[1]), Retention::MinDays(7));
+        guild.retention_days = Some(14);
+        assert_eq!(
+            guild.reward_retention(&guild.rewards[0]),
+            Retention::MinDays(14)
+        );
+    }
+
+    #[test]
+    fn test_retention() {
+        let mut guild = AllowedGuild::default();
+        assert_eq!(guild.retention(), Retention::All);
+
+        guild.count_mode = Some(CountMode::Present);
+        assert_eq!(guild.retention(), Retention::Present);
+
+        // 未設定天數時預設 7 天
+        guild.count_mode = Some(CountMode::Retained);
+        assert_eq!(guild.retention(), Retention::MinDays(7));
+        guild.retention_days = Some(14);
+        assert_eq!(guild.retention(), Retention::MinDays(14));
+    }
+
+    #[test]
+    fn test_redacted_secrets() {
+        let mut config = create_test_config().1;
+        config.server.admin_token = Some("admin_secret".to_string());
+
+        let mut redacted = config.redacted();
+        assert_eq!(redacted.bot.token, REDACTED);
+        assert_eq!(redacted.server.admin_token.as_deref(), Some(REDACTED));
+
+        // 遮蔽的值在儲存時應還原
+        redacted.restore_secrets(&config);
+        assert_eq!(redacted.bot.token, "test_token");
+        assert_eq!(redacted.server.admin_token.as_deref(), Some("admin_secret"));
+
+        // 新的值則保留
+        redacted.bot.token = "new_token".to_string();
+        redacted.restore_secrets(&config);
+        assert_eq!(redacted.bot.token, "new_token");
+    }
+
+    #[test]
+    fn test_shared_config_reload() {
+        let (file, config) = create_test_config();
+        let shared = SharedConfig::new(config.clone());
+        let snapshot = shared.get();
+
+        let updated = Config {
+            i18n: I18nConfig {
+                default_locale: "zh-TW".to_string(),
+            },
+            ..config
+        };
+        updated.save(file.path().to_str().unwrap()).unwrap();
+        shared.reload(file.path().to_str().unwrap()).unwrap();
+
+        // 舊的快照不受影響，新的讀取會看到更新
+        assert_eq!(snapshot.i18n.default_locale, "en");
+        assert_eq!(shared.get().i18n.default_locale, "zh-TW");
+    }
+
+    #[test]
+    fn test_shared_config_reload_keeps_old_on_error() {
+        let (file, config) = create_test_config();
+        let shared = SharedConfig::new(config);
+
+        fs::write(file.path(), "not: [valid").unwrap();
+        assert!(shared.reload(file.path().to_str().unwrap()).is_err());
+        assert_eq!(shared.get().bot.token, "test_token");
+    }
+
     #[test]
     fn test_default_values() {
         let config = create_test_config().1;
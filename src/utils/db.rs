@@ -1,35 +1,65 @@
+use crate::utils::config::{AllowedGuild, Retention};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
 use sqlx::types::time::OffsetDateTime;
 type Pool = sqlx::Pool<sqlx::Sqlite>;
 
-pub async fn setup_database(pool: &Pool) -> Result<(), sqlx::Error> {
-    let migrations = crate::migrations::get_migrations();
-    for migration in migrations {
-        if let Some(sql) = crate::migrations::get_migration(migration.as_str()) {
-            sqlx::query(&sql).execute(pool).await?;
-        }
-    }
+use crate::migrations::MigrationError;
+
+pub async fn setup_database(pool: &Pool) -> Result<(), MigrationError> {
+    crate::migrations::run(pool).await?;
     Ok(())
 }
 
-pub async fn create_pool(database_path: &str) -> Result<Pool, sqlx::Error> {
-    let pool = Pool::connect(database_path).await?;
+pub async fn connect(database_path: &str) -> Result<Pool, sqlx::Error> {
+    Pool::connect(database_path).await
+}
+
+pub async fn create_pool(database_path: &str) -> Result<Pool, MigrationError> {
+    let pool = connect(database_path).await?;
     setup_database(&pool).await?;
     Ok(pool)
 }
 
-pub async fn create_invite(
-    pool: &Pool,
-    invite_id: &str,
-    guild_id: &str,
-    creator_id: &str,
-) -> Result<(), sqlx::Error> {
+#[derive(Debug, Default)]
+pub struct NewInvite<'a> {
+    pub id: &'a str,
+    pub guild_id: &'a str,
+    pub creator_id: &'a str,
+    pub max_uses: u8,
+    /// Seconds until the bot link expires, `None` to keep it forever.
+    pub link_ttl: Option<u32>,
+    /// Hold the link until a moderator approves it.
+    pub pending_approval: bool,
+    /// The requester's reason or intended invitee, for reviewers.
+    pub approval_note: Option<&'a str>,
+    /// The only account meant to join through the link, as stored by
+    /// [`crate::utils::reservation::normalize`].
+    pub intended_for: Option<&'a str>,
+    /// Comma-separated role IDs to give whoever joins through the link.
+    pub grant_roles: Option<&'a str>,
+}
+
+pub async fn create_invite(pool: &Pool, invite: &NewInvite<'_>) -> Result<(), sqlx::Error> {
+    // datetime('now', NULL) is NULL, which leaves the link without expiry
+    let ttl = invite.link_ttl.map(|ttl| format!("+{} seconds", ttl));
     sqlx::query!(
         "INSERT INTO invites (
-            id, guild_id, creator_id, created_at
-        ) VALUES (?, ?, ?, datetime('now'))",
-        invite_id,
-        guild_id,
-        creator_id
+            id, guild_id, creator_id, created_at, max_uses, expires_at,
+            approval_status, approval_note, intended_for, grant_roles
+        ) VALUES (
+            ?, ?, ?, datetime('now'), ?, datetime('now', ?),
+            CASE WHEN ? THEN 'pending' END, ?, ?, ?
+        )",
+        invite.id,
+        invite.guild_id,
+        invite.creator_id,
+        invite.max_uses,
+        ttl,
+        invite.pending_approval,
+        invite.approval_note,
+        invite.intended_for,
+        invite.grant_roles
     )
     .execute(pool)
     .await?;
@@ -40,9 +70,26 @@ pub async fn get_unused_invite(
     pool: &Pool,
     invite_id: &str,
 ) -> Result<Option<InviteRecord>, sqlx::Error> {
+    // Multi-use links stay redeemable until every use is taken
     sqlx::query_as!(
         InviteRecord,
-        "SELECT guild_id, creator_id, discord_invite_code as code FROM invites WHERE id = ? AND used_at IS NULL",
+        r#"
+        SELECT guild_id, creator_id, discord_invite_code as code, max_uses as "max_uses: u8"
+        FROM invites
+        WHERE id = ?
+        AND parent_id IS NULL
+        AND revoked_at IS NULL
+        AND (approval_status IS NULL OR approval_status = 'approved')
+        AND (expires_at IS NULL OR expires_at > datetime('now'))
+        AND (
+            used_at IS NULL
+            OR max_uses > (
+                SELECT COUNT(*) FROM invites u
+                WHERE (u.id = invites.id OR u.parent_id = invites.id)
+                AND u.used_at IS NOT NULL
+            )
+        )
+        "#,
         invite_id
     )
     .fetch_optional(pool)
@@ -64,22 +111,67 @@ pub async fn update_invite_code(
     Ok(())
 }
 
+impl Retention {
+    /// Binds for the `(? OR left_at IS NULL OR left_at >= datetime(used_at, ?))` filter.
+    fn sql_params(&self) -> (bool, Option<String>) {
+        match self {
+            Retention::All => (true, None),
+            Retention::Present => (false, None),
+            Retention::MinDays(days) => (false, Some(format!("+{} days", days))),
+        }
+    }
+}
+
 pub async fn count_used_invites(
     pool: &Pool,
     creator_id: &str,
     guild_id: &str,
     days: i32,
+    retention: Retention,
 ) -> Result<i64, sqlx::Error> {
     let days_str = format!("-{} days", days);
+    let (count_all, min_stay) = retention.sql_params();
     let count = sqlx::query_scalar!(
         "SELECT COUNT(*) as count 
          FROM invites 
          WHERE creator_id = ? 
          AND created_at > datetime('now', ?) 
          AND guild_id = ?
-         AND used_at IS NOT NULL",
+         AND used_at IS NOT NULL
+         AND source IS NULL
+         AND (? OR left_at IS NULL OR left_at >= datetime(used_at, ?))",
         creator_id,
         days_str,
+        guild_id,
+        count_all,
+        min_stay
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count)
+}
+
+/// Counts links that are still redeemable: not revoked or expired, with uses left.
+pub async fn count_outstanding_invites(
+    pool: &Pool,
+    creator_id: &str,
+    guild_id: &str,
+) -> Result<i64, sqlx::Error> {
+    let count = sqlx::query_scalar!(
+        "SELECT COUNT(*) as count
+         FROM invites
+         WHERE creator_id = ?
+         AND guild_id = ?
+         AND parent_id IS NULL
+         AND revoked_at IS NULL
+         AND (expires_at IS NULL OR expires_at > datetime('now'))
+         AND max_uses > (
+             SELECT COUNT(*) FROM invites u
+             WHERE (u.id = invites.id OR u.parent_id = invites.id)
+             AND u.used_at IS NOT NULL
+         )",
+        creator_id,
         guild_id
     )
     .fetch_one(pool)
@@ -88,12 +180,317 @@ pub async fn count_used_invites(
     Ok(count)
 }
 
+/// A member's links in a guild by status, plus how many invitees are still around.
+#[derive(Debug, Default, PartialEq, Eq, sqlx::FromRow)]
+pub struct InviteStats {
+    pub created: i64,
+    pub used: i64,
+    pub pending: i64,
+    pub expired: i64,
+    pub revoked: i64,
+    pub retained: i64,
+}
+
+pub async fn get_invite_stats(
+    pool: &Pool,
+    guild_id: &str,
+    creator_id: &str,
+) -> Result<InviteStats, sqlx::Error> {
+    // Each link is counted once: revoked, else used, else expired, else pending
+    sqlx::query_as!(
+        InviteStats,
+        r#"
+        SELECT
+            COALESCE(SUM(parent_id IS NULL), 0) as "created!: i64",
+            COALESCE(SUM(parent_id IS NULL AND revoked_at IS NULL AND used_at IS NOT NULL), 0)
+                as "used!: i64",
+            COALESCE(SUM(
+                parent_id IS NULL AND revoked_at IS NULL AND used_at IS NULL
+                AND (expires_at IS NULL OR expires_at > datetime('now'))
+            ), 0) as "pending!: i64",
+            COALESCE(SUM(
+                parent_id IS NULL AND revoked_at IS NULL AND used_at IS NULL
+                AND expires_at <= datetime('now')
+            ), 0) as "expired!: i64",
+            COALESCE(SUM(parent_id IS NULL AND revoked_at IS NOT NULL), 0) as "revoked!: i64",
+            COALESCE(SUM(used_at IS NOT NULL AND left_at IS NULL), 0) as "retained!: i64"
+        FROM invites
+        WHERE guild_id = ?
+        AND creator_id = ?
+        "#,
+        guild_id,
+        creator_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct RecentInvitee {
+    pub user_id: String,
+    pub used_at: OffsetDateTime,
+    pub left_at: Option<OffsetDateTime>,
+}
+
+pub async fn get_recent_invitees(
+    pool: &Pool,
+    guild_id: &str,
+    creator_id: &str,
+    limit: i64,
+) -> Result<Vec<RecentInvitee>, sqlx::Error> {
+    sqlx::query_as!(
+        RecentInvitee,
+        r#"
+        SELECT used_by as "user_id!", used_at as "used_at!", left_at
+        FROM invites
+        WHERE guild_id = ?
+        AND creator_id = ?
+        AND used_at IS NOT NULL
+        ORDER BY used_at DESC
+        LIMIT ?
+        "#,
+        guild_id,
+        creator_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Where a link stands. A link is revoked, else used, else expired, else pending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InviteStatus {
+    Pending,
+    Used,
+    Expired,
+    Revoked,
+}
+
+impl InviteStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InviteStatus::Pending => "pending",
+            InviteStatus::Used => "used",
+            InviteStatus::Expired => "expired",
+            InviteStatus::Revoked => "revoked",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct InviteFilter<'a> {
+    pub guild_id: Option<&'a str>,
+    pub creator_id: Option<&'a str>,
+    pub status: Option<InviteStatus>,
+    /// First day of the range, inclusive.
+    pub created_from: Option<NaiveDate>,
+    /// Last day of the range, inclusive.
+    pub created_to: Option<NaiveDate>,
+    /// `id` of the last invite of the previous page.
+    pub after: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct InviteListEntry {
+    pub id: String,
+    pub guild_id: String,
+    pub creator_id: String,
+    pub created_at: NaiveDateTime,
+    /// One of the [`InviteStatus`] names.
+    pub status: String,
+    pub uses: i64,
+    pub max_uses: i64,
+    pub expires_at: Option<NaiveDateTime>,
+    pub discord_invite_code: Option<String>,
+}
+
+/// Links matching `filter`, newest first, at most `limit` of them.
+pub async fn list_invites(
+    pool: &Pool,
+    filter: &InviteFilter<'_>,
+    limit: i64,
+) -> Result<Vec<InviteListEntry>, sqlx::Error> {
+    let status = filter.status.map(|status| status.as_str());
+    sqlx::query_as!(
+        InviteListEntry,
+        r#"
+        WITH listed AS (
+            SELECT
+                id, guild_id, creator_id, created_at, max_uses, expires_at, discord_invite_code,
+                CASE
+                    WHEN revoked_at IS NOT NULL THEN 'revoked'
+                    WHEN used_at IS NOT NULL THEN 'used'
+                    WHEN expires_at <= datetime('now') THEN 'expired'
+                    ELSE 'pending'
+                END AS status,
+                (
+                    SELECT COUNT(*) FROM invites u
+                    WHERE (u.id = invites.id OR u.parent_id = invites.id)
+                    AND u.used_at IS NOT NULL
+                ) AS uses
+            FROM invites
+            WHERE parent_id IS NULL
+        )
+        SELECT
+            id as "id!",
+            guild_id,
+            creator_id,
+            created_at as "created_at!: NaiveDateTime",
+            status as "status!: String",
+            uses as "uses!: i64",
+            max_uses,
+            expires_at as "expires_at: NaiveDateTime",
+            discord_invite_code
+        FROM listed
+        WHERE (?1 IS NULL OR guild_id = ?1)
+        AND (?2 IS NULL OR creator_id = ?2)
+        AND (?3 IS NULL OR status = ?3)
+        AND (?4 IS NULL OR created_at >= datetime(?4))
+        AND (?5 IS NULL OR created_at < datetime(?5, '+1 day'))
+        AND (?6 IS NULL OR (created_at, id) < (SELECT created_at, id FROM invites WHERE id = ?6))
+        ORDER BY created_at DESC, id DESC
+        LIMIT ?7
+        "#,
+        filter.guild_id,
+        filter.creator_id,
+        status,
+        filter.created_from,
+        filter.created_to,
+        filter.after,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// When the guild's scheduled leaderboard was last posted, `None` if never.
+pub async fn get_last_announcement(
+    pool: &Pool,
+    guild_id: &str,
+) -> Result<Option<NaiveDateTime>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT last_posted_at as "last_posted_at: NaiveDateTime"
+         FROM leaderboard_announcements
+         WHERE guild_id = ?"#,
+        guild_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn set_last_announcement(
+    pool: &Pool,
+    guild_id: &str,
+    posted_at: NaiveDateTime,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO leaderboard_announcements (guild_id, last_posted_at)
+         VALUES (?1, datetime(?2))
+         ON CONFLICT (guild_id) DO UPDATE SET last_posted_at = datetime(?2)",
+        guild_id,
+        posted_at
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// A join recorded by another invite tracker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedInvite {
+    pub guild_id: String,
+    pub inviter_id: String,
+    pub invitee_id: String,
+    pub joined_at: NaiveDateTime,
+}
+
+/// Stores an imported join as a used, single-use link marked `imported`.
+/// Returns false if the same join was imported before.
+pub async fn import_invite(pool: &Pool, invite: &ImportedInvite) -> Result<bool, sqlx::Error> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO invites (
+            id, guild_id, creator_id, created_at, used_at, used_by, max_uses, source
+        )
+        SELECT ?1, ?2, ?3, datetime(?4), datetime(?4), ?5, 1, 'imported'
+        WHERE NOT EXISTS (
+            SELECT 1 FROM invites
+            WHERE guild_id = ?2
+            AND used_by = ?5
+            AND used_at = datetime(?4)
+            AND source = 'imported'
+        )
+        "#,
+        id,
+        invite.guild_id,
+        invite.inviter_id,
+        invite.joined_at,
+        invite.invitee_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// One row of the export: an unused link, or one use of a link.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct InviteHistoryEntry {
+    /// The link, shared by every use of a multi-use link.
+    pub invite_id: String,
+    pub creator_id: String,
+    pub invitee_id: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub used_at: Option<NaiveDateTime>,
+    pub left_at: Option<NaiveDateTime>,
+    pub discord_invite_code: Option<String>,
+    /// `bot` for links the bot made, `imported` for history from another tracker.
+    pub source: String,
+}
+
+/// Invite history of a guild, oldest first. The range applies to the day of
+/// the join, or of the creation for links nobody used.
+pub async fn get_invite_history(
+    pool: &Pool,
+    guild_id: &str,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<Vec<InviteHistoryEntry>, sqlx::Error> {
+    sqlx::query_as!(
+        InviteHistoryEntry,
+        r#"
+        SELECT
+            COALESCE(parent_id, id) as "invite_id!: String",
+            creator_id,
+            used_by as invitee_id,
+            created_at as "created_at!: NaiveDateTime",
+            used_at as "used_at: NaiveDateTime",
+            left_at as "left_at: NaiveDateTime",
+            discord_invite_code,
+            COALESCE(source, 'bot') as "source!: String"
+        FROM invites
+        WHERE guild_id = ?1
+        AND (?2 IS NULL OR COALESCE(used_at, created_at) >= datetime(?2))
+        AND (?3 IS NULL OR COALESCE(used_at, created_at) < datetime(?3, '+1 day'))
+        ORDER BY COALESCE(used_at, created_at), id
+        "#,
+        guild_id,
+        from,
+        to
+    )
+    .fetch_all(pool)
+    .await
+}
+
 #[derive(Debug)]
 #[allow(dead_code)]
 pub struct InviteRecord {
     pub guild_id: String,
     pub creator_id: String,   // Used for invite tracking and permissions
     pub code: Option<String>, // Discord invite code, if already created
+    pub max_uses: u8,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -105,28 +502,100 @@ pub struct InviteInfo {
 
 pub async fn get_user_invite_info(
     pool: &Pool,
+    guild_id: &str,
     user_id: &str,
 ) -> Result<Option<InviteInfo>, sqlx::Error> {
     sqlx::query_as!(
         InviteInfo,
         "SELECT creator_id, used_at, discord_invite_code
          FROM invites 
-         WHERE used_by = ? 
+         WHERE guild_id = ?
+         AND used_by = ? 
+         AND used_at IS NOT NULL
+         ORDER BY used_at DESC
+         LIMIT 1",
+        guild_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Who invited a member to a guild, going by their latest recorded join there.
+pub async fn get_inviter(
+    pool: &Pool,
+    guild_id: &str,
+    user_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT creator_id FROM invites
+         WHERE guild_id = ?
+         AND used_by = ?
          AND used_at IS NOT NULL
          ORDER BY used_at DESC
          LIMIT 1",
+        guild_id,
         user_id
     )
     .fetch_optional(pool)
     .await
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct InviteeRecord {
+    pub user_id: String,
+    pub inviter_id: String,
+    /// 1 for people `root` invited directly, 2 for the people they invited, and so on.
+    pub depth: i64,
+}
+
+/// Everyone `root` brought into the guild, directly or through their invitees, up to
+/// `max_depth` levels down. A member who joined several times is listed once, under
+/// the invite closest to `root`.
+pub async fn get_invitee_tree(
+    pool: &Pool,
+    guild_id: &str,
+    root: &str,
+    max_depth: i64,
+) -> Result<Vec<InviteeRecord>, sqlx::Error> {
+    // UNION drops repeated rows, which together with the depth cap ends invite cycles
+    sqlx::query_as!(
+        InviteeRecord,
+        r#"
+        WITH RECURSIVE tree(user_id, inviter_id, depth, used_at) AS (
+            SELECT used_by, creator_id, 1, used_at
+            FROM invites
+            WHERE guild_id = ?1
+            AND creator_id = ?2
+            AND used_by IS NOT NULL
+            UNION
+            SELECT invites.used_by, invites.creator_id, tree.depth + 1, invites.used_at
+            FROM invites
+            JOIN tree ON invites.creator_id = tree.user_id
+            WHERE invites.guild_id = ?1
+            AND invites.used_by IS NOT NULL
+            AND tree.depth < ?3
+        )
+        SELECT user_id as "user_id!", inviter_id as "inviter_id!", MIN(depth) as "depth!: i64"
+        FROM tree
+        WHERE user_id != ?2
+        GROUP BY user_id
+        ORDER BY MIN(depth), MIN(used_at)
+        "#,
+        guild_id,
+        root,
+        max_depth
+    )
+    .fetch_all(pool)
+    .await
+}
+
 pub async fn record_invite_use(
     pool: &Pool,
     invite_id: &str,
     user_id: &str,
 ) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+    let result = sqlx::query!(
         r#"
         UPDATE invites 
         SET used_at = datetime('now'), 
@@ -139,15 +608,161 @@ pub async fn record_invite_use(
     )
     .execute(pool)
     .await?;
+
+    // Later uses of a multi-use link are recorded as rows of their own
+    if result.rows_affected() == 0 {
+        let use_id = uuid::Uuid::new_v4().to_string();
+        sqlx::query!(
+            r#"
+            INSERT INTO invites (
+                id, guild_id, creator_id, created_at, used_at, used_by,
+                discord_invite_code, max_uses, parent_id
+            )
+            SELECT ?, guild_id, creator_id, created_at, datetime('now'), ?,
+                discord_invite_code, max_uses, id
+            FROM invites
+            WHERE id = ?
+            AND max_uses > 1
+            "#,
+            use_id,
+            user_id,
+            invite_id
+        )
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Counts everyone a member has brought into the guild, including imported invites.
+pub async fn count_invitees(
+    pool: &Pool,
+    guild_id: &str,
+    creator_id: &str,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM invites
+         WHERE guild_id = ?
+         AND creator_id = ?
+         AND used_at IS NOT NULL",
+        guild_id,
+        creator_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Counts a member's invitees towards a reward tier: those who joined in the last
+/// `days`, or ever, through a bot invite.
+pub async fn count_reward_invitees(
+    pool: &Pool,
+    guild_id: &str,
+    creator_id: &str,
+    days: Option<u32>,
+    retention: Retention,
+) -> Result<i64, sqlx::Error> {
+    let days_str = days.map(|days| format!("-{} days", days));
+    let (count_all, min_stay) = retention.sql_params();
+    sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM invites
+         WHERE guild_id = ?1
+         AND creator_id = ?2
+         AND used_at IS NOT NULL
+         AND source IS NULL
+         AND (?3 IS NULL OR used_at > datetime('now', ?3))
+         AND (?4 OR left_at IS NULL OR left_at >= datetime(used_at, ?5))",
+        guild_id,
+        creator_id,
+        days_str,
+        count_all,
+        min_stay
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Everyone in a guild with at least one invitee that can count towards a reward tier.
+pub async fn get_reward_inviters(pool: &Pool, guild_id: &str) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT DISTINCT creator_id FROM invites
+         WHERE guild_id = ?
+         AND used_at IS NOT NULL
+         AND source IS NULL",
+        guild_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Marks the invite a member joined through as left. Returns false if none was recorded.
+pub async fn record_member_left(
+    pool: &Pool,
+    guild_id: &str,
+    user_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "UPDATE invites
+         SET left_at = datetime('now')
+         WHERE guild_id = ?
+         AND used_by = ?
+         AND used_at IS NOT NULL
+         AND left_at IS NULL",
+        guild_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Stores the alt-account signals of a flagged join on the use it was recorded as.
+pub async fn flag_invite_use(
+    pool: &Pool,
+    invite_id: &str,
+    user_id: &str,
+    flags: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE invites
+         SET flags = ?
+         WHERE (id = ? OR parent_id = ?)
+         AND used_by = ?",
+        flags,
+        invite_id,
+        invite_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
     Ok(())
 }
 
+/// Counts an inviter's flagged invitees, across all time.
+pub async fn count_flagged_invitees(
+    pool: &Pool,
+    guild_id: &str,
+    creator_id: &str,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT COUNT(*) as count
+         FROM invites
+         WHERE guild_id = ?
+         AND creator_id = ?
+         AND flags IS NOT NULL",
+        guild_id,
+        creator_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Finds the bot invite a Discord code was created for.
 pub async fn find_invite_by_code(
     pool: &Pool,
     discord_code: &str,
 ) -> Result<Option<String>, sqlx::Error> {
     sqlx::query_scalar!(
-        "SELECT id FROM invites WHERE discord_invite_code = ? AND used_at IS NULL LIMIT 1",
+        "SELECT id FROM invites WHERE discord_invite_code = ? AND parent_id IS NULL LIMIT 1",
         discord_code
     )
     .fetch_optional(pool)
@@ -156,19 +771,455 @@ pub async fn find_invite_by_code(
 }
 
 #[derive(Debug, sqlx::FromRow)]
-pub struct InviteLeaderboardEntry {
+pub struct InviteDetails {
+    pub guild_id: String,
     pub creator_id: String,
-    pub invite_count: i64,
+    pub used_at: Option<OffsetDateTime>,
+    pub revoked_at: Option<OffsetDateTime>,
+    pub expires_at: Option<OffsetDateTime>,
+    pub discord_invite_code: Option<String>,
+    pub max_uses: i64,
+    pub approval_status: Option<String>,
+    pub intended_for: Option<String>,
+    pub grant_roles: Option<String>,
 }
 
-pub async fn get_invite_leaderboard(
-    pool: &Pool,
-    guild_id: &str,
-    days: i32,
+impl InviteDetails {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= OffsetDateTime::now_utc())
+    }
+
+    pub fn is_awaiting_approval(&self) -> bool {
+        self.approval_status.as_deref() == Some("pending") && self.revoked_at.is_none()
+    }
+}
+
+pub async fn get_invite(
+    pool: &Pool,
+    invite_id: &str,
+) -> Result<Option<InviteDetails>, sqlx::Error> {
+    sqlx::query_as!(
+        InviteDetails,
+        r#"
+        SELECT
+            guild_id, creator_id, used_at, revoked_at, expires_at, discord_invite_code, max_uses,
+            approval_status, intended_for, grant_roles
+        FROM invites
+        WHERE id = ?
+        AND parent_id IS NULL
+        "#,
+        invite_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ReviewedInvite {
+    pub guild_id: String,
+    pub creator_id: String,
+    pub approval_note: Option<String>,
+}
+
+/// Approves or denies a link awaiting approval; a denied link is revoked, and an
+/// approved one gets its full `link_ttl` from now. Returns `None` if the link isn't
+/// awaiting approval, e.g. because someone else reviewed it.
+pub async fn review_invite(
+    pool: &Pool,
+    invite_id: &str,
+    approve: bool,
+    reviewed_by: &str,
+) -> Result<Option<ReviewedInvite>, sqlx::Error> {
+    sqlx::query_as!(
+        ReviewedInvite,
+        r#"
+        UPDATE invites
+        SET approval_status = CASE WHEN ?2 THEN 'approved' ELSE 'denied' END,
+            reviewed_by = ?3,
+            expires_at = CASE WHEN ?2 AND expires_at IS NOT NULL
+                THEN datetime(
+                    'now',
+                    '+' || (strftime('%s', expires_at) - strftime('%s', created_at)) || ' seconds'
+                )
+                ELSE expires_at
+            END,
+            revoked_at = CASE WHEN ?2 THEN revoked_at ELSE datetime('now') END,
+            revoked_by = CASE WHEN ?2 THEN revoked_by ELSE ?3 END
+        WHERE id = ?1
+        AND parent_id IS NULL
+        AND approval_status = 'pending'
+        AND revoked_at IS NULL
+        RETURNING guild_id as "guild_id!", creator_id as "creator_id!", approval_note
+        "#,
+        invite_id,
+        approve,
+        reviewed_by
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Marks an invite as revoked. Returns false if it was already revoked.
+pub async fn revoke_invite(
+    pool: &Pool,
+    invite_id: &str,
+    revoked_by: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "UPDATE invites
+         SET revoked_at = datetime('now'), revoked_by = ?
+         WHERE id = ?
+         AND revoked_at IS NULL",
+        revoked_by,
+        invite_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ExpiredInvite {
+    pub id: String,
+    pub guild_id: String,
+    pub discord_invite_code: String,
+}
+
+/// Expired links whose Discord invite may still be redeemed: unused, or a
+/// multi-use link with uses left, and not swept yet.
+pub async fn get_expired_invites(pool: &Pool) -> Result<Vec<ExpiredInvite>, sqlx::Error> {
+    sqlx::query_as!(
+        ExpiredInvite,
+        r#"
+        SELECT id as "id!", guild_id, discord_invite_code as "discord_invite_code!"
+        FROM invites
+        WHERE parent_id IS NULL
+        AND revoked_at IS NULL
+        AND swept_at IS NULL
+        AND discord_invite_code IS NOT NULL
+        AND expires_at <= datetime('now')
+        AND (
+            used_at IS NULL
+            OR max_uses > (
+                SELECT COUNT(*) FROM invites u
+                WHERE (u.id = invites.id OR u.parent_id = invites.id)
+                AND u.used_at IS NOT NULL
+            )
+        )
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Records that an expired link's Discord invite was deleted, keeping its code.
+pub async fn mark_invite_swept(pool: &Pool, invite_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE invites SET swept_at = datetime('now') WHERE id = ?",
+        invite_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Whether a join of this user was recorded in the guild before.
+pub async fn has_joined_before(
+    pool: &Pool,
+    guild_id: &str,
+    user_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let count = sqlx::query_scalar!(
+        "SELECT COUNT(*) as count
+         FROM join_attributions
+         WHERE guild_id = ?
+         AND user_id = ?",
+        guild_id,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(count > 0)
+}
+
+/// Records how a join was attributed, including ambiguous and unknown outcomes.
+pub async fn record_join_attribution(
+    pool: &Pool,
+    guild_id: &str,
+    user_id: &str,
+    status: &str,
+    invite_codes: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO join_attributions (guild_id, user_id, status, invite_codes)
+         VALUES (?, ?, ?, ?)",
+        guild_id,
+        user_id,
+        status,
+        invite_codes
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+pub struct NewOverride<'a> {
+    pub guild_id: &'a str,
+    pub user_id: &'a str,
+    /// `extra`, `limit` or `deny`.
+    pub kind: &'a str,
+    pub count: Option<i32>,
+    pub days: Option<i32>,
+    pub created_by: &'a str,
+    /// Days until the override lapses, `None` to keep it until reset.
+    pub duration_days: Option<u32>,
+}
+
+/// Sets a member's override, replacing any previous one.
+pub async fn set_override(pool: &Pool, new: &NewOverride<'_>) -> Result<(), sqlx::Error> {
+    let duration = new.duration_days.map(|days| format!("+{} days", days));
+    sqlx::query!(
+        "INSERT INTO invite_overrides (
+            guild_id, user_id, kind, count, days, created_by, created_at, expires_at
+        ) VALUES (?, ?, ?, ?, ?, ?, datetime('now'), datetime('now', ?))
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET
+            kind = excluded.kind,
+            count = excluded.count,
+            days = excluded.days,
+            created_by = excluded.created_by,
+            created_at = excluded.created_at,
+            expires_at = excluded.expires_at",
+        new.guild_id,
+        new.user_id,
+        new.kind,
+        new.count,
+        new.days,
+        new.created_by,
+        duration
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Takes `amount` invites off a member's quota, on top of any override they have.
+/// A denied member stays denied.
+pub async fn add_quota_penalty(
+    pool: &Pool,
+    guild_id: &str,
+    user_id: &str,
+    amount: u32,
+    created_by: &str,
+) -> Result<(), sqlx::Error> {
+    let extra = -(amount as i64);
+    // Column names in DO UPDATE refer to the existing row; an expired one is replaced
+    sqlx::query!(
+        "INSERT INTO invite_overrides (
+            guild_id, user_id, kind, count, created_by, created_at
+        ) VALUES (?, ?, 'extra', ?, ?, datetime('now'))
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET
+            kind = CASE WHEN expires_at <= datetime('now') THEN 'extra' ELSE kind END,
+            count = CASE
+                WHEN expires_at <= datetime('now') THEN excluded.count
+                WHEN kind = 'deny' THEN count
+                ELSE count + excluded.count
+            END,
+            days = CASE WHEN expires_at <= datetime('now') THEN NULL ELSE days END,
+            expires_at = CASE WHEN expires_at <= datetime('now') THEN NULL ELSE expires_at END",
+        guild_id,
+        user_id,
+        extra,
+        created_by
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Bars a member from creating invites for `days`, keeping a longer suspension
+/// already in place. Their override, if any, is left alone.
+pub async fn suspend_invites(
+    pool: &Pool,
+    guild_id: &str,
+    user_id: &str,
+    days: u32,
+    created_by: &str,
+) -> Result<(), sqlx::Error> {
+    let duration = format!("+{} days", days);
+    sqlx::query!(
+        "INSERT INTO invite_suspensions (
+            guild_id, user_id, suspended_until, created_by, created_at
+        ) VALUES (?, ?, datetime('now', ?), ?, datetime('now'))
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET
+            suspended_until = MAX(suspended_until, excluded.suspended_until),
+            created_by = excluded.created_by,
+            created_at = excluded.created_at",
+        guild_id,
+        user_id,
+        duration,
+        created_by
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn is_suspended(pool: &Pool, guild_id: &str, user_id: &str) -> Result<bool, sqlx::Error> {
+    let suspended = sqlx::query_scalar!(
+        r#"SELECT EXISTS (
+            SELECT 1 FROM invite_suspensions
+            WHERE guild_id = ?
+            AND user_id = ?
+            AND suspended_until > datetime('now')
+        ) as "suspended!: bool""#,
+        guild_id,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(suspended)
+}
+
+/// Ends a member's suspension early. Returns false if there was none.
+pub async fn lift_suspension(
+    pool: &Pool,
+    guild_id: &str,
+    user_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "DELETE FROM invite_suspensions
+         WHERE guild_id = ?
+         AND user_id = ?
+         AND suspended_until > datetime('now')",
+        guild_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct InviteOverride {
+    pub kind: String,
+    pub count: Option<i64>,
+    pub days: Option<i64>,
+}
+
+/// A member's override, unless it has expired.
+pub async fn get_active_override(
+    pool: &Pool,
+    guild_id: &str,
+    user_id: &str,
+) -> Result<Option<InviteOverride>, sqlx::Error> {
+    sqlx::query_as!(
+        InviteOverride,
+        "SELECT kind, count, days
+         FROM invite_overrides
+         WHERE guild_id = ?
+         AND user_id = ?
+         AND (expires_at IS NULL OR expires_at > datetime('now'))",
+        guild_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Removes a member's override. Returns false if there was none.
+pub async fn delete_override(
+    pool: &Pool,
+    guild_id: &str,
+    user_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "DELETE FROM invite_overrides WHERE guild_id = ? AND user_id = ?",
+        guild_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct InviteLeaderboardEntry {
+    pub creator_id: String,
+    pub invite_count: i64,
+}
+
+/// What the leaderboard ranks inviters by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LeaderboardMetric {
+    /// Invitees who joined, subject to the retention and flag filters.
+    #[default]
+    Used,
+    /// Invitees who are still members, or under the `retained` count mode stayed
+    /// `retention_days`.
+    Retained,
+    /// Links created, used or not.
+    Created,
+}
+
+impl LeaderboardMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeaderboardMetric::Used => "used",
+            LeaderboardMetric::Retained => "retained",
+            LeaderboardMetric::Created => "created",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LeaderboardQuery {
+    pub days: i32,
+    /// Days before now the window ends, to rank an earlier period.
+    pub offset_days: i32,
+    pub metric: LeaderboardMetric,
+    pub retention: Retention,
+    pub exclude_flagged: bool,
+    /// Count rows imported from another tracker.
+    pub include_imported: bool,
+}
+
+impl LeaderboardQuery {
+    /// A query over the last `days` with the guild's counting rules.
+    pub fn for_guild(
+        guild_config: Option<&AllowedGuild>,
+        days: i32,
+        metric: LeaderboardMetric,
+    ) -> Self {
+        LeaderboardQuery {
+            days,
+            offset_days: 0,
+            metric,
+            retention: guild_config.map(|g| g.retention()).unwrap_or_default(),
+            exclude_flagged: guild_config
+                .and_then(|g| g.alt_detection.as_ref())
+                .is_some_and(|alt_detection| alt_detection.exclude_from_leaderboard),
+            include_imported: guild_config
+                .and_then(|g| g.leaderboard_include_imported)
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Every inviter with a non-zero count, best first.
+pub async fn get_invite_leaderboard(
+    pool: &Pool,
+    guild_id: &str,
+    query: &LeaderboardQuery,
 ) -> Result<Vec<InviteLeaderboardEntry>, sqlx::Error> {
     // Ensure days is non-negative
-    let days = days.max(0);
+    let offset = query.offset_days.max(0);
+    let days = query.days.max(0) + offset;
     let days_str = format!("-{} days", days);
+    let offset_str = format!("-{} days", offset);
+    let metric = query.metric.as_str();
+    let (count_all, min_stay) = query.retention.sql_params();
 
     sqlx::query_as!(
         InviteLeaderboardEntry,
@@ -177,15 +1228,30 @@ pub async fn get_invite_leaderboard(
             creator_id,
             COUNT(*) as invite_count
         FROM invites 
-        WHERE guild_id = ?
-        AND created_at > datetime('now', ?)
-        AND used_at IS NOT NULL
+        WHERE guild_id = ?1
+        AND created_at > datetime('now', ?2)
+        AND created_at <= datetime('now', ?8)
+        AND CASE ?3
+            WHEN 'created' THEN parent_id IS NULL
+            WHEN 'retained' THEN used_at IS NOT NULL
+                AND (left_at IS NULL OR (?5 IS NOT NULL AND left_at >= datetime(used_at, ?5)))
+                AND (NOT ?6 OR flags IS NULL)
+            ELSE used_at IS NOT NULL
+                AND (?4 OR left_at IS NULL OR left_at >= datetime(used_at, ?5))
+                AND (NOT ?6 OR flags IS NULL)
+        END
+        AND (?7 OR source IS NULL)
         GROUP BY creator_id
         ORDER BY invite_count DESC, creator_id ASC
-        LIMIT 5
         "#,
         guild_id,
-        days_str
+        days_str,
+        metric,
+        count_all,
+        min_stay,
+        query.exclude_flagged,
+        query.include_imported,
+        offset_str
     )
     .fetch_all(pool)
     .await
@@ -210,9 +1276,18 @@ mod tests {
         let creator_id = "987654321";
 
         // Test create invite
-        create_invite(&pool, &invite_id, guild_id, creator_id)
-            .await
-            .unwrap();
+        create_invite(
+            &pool,
+            &NewInvite {
+                id: &invite_id,
+                guild_id,
+                creator_id,
+                max_uses: 1,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
 
         // Test get unused invite
         let invite = get_unused_invite(&pool, &invite_id).await.unwrap().unwrap();
@@ -231,19 +1306,55 @@ mod tests {
         // Create multiple invites
         for _ in 0..3 {
             let invite_id = Uuid::new_v4().to_string();
-            create_invite(&pool, &invite_id, guild_id, creator_id)
-                .await
-                .unwrap();
+            create_invite(
+                &pool,
+                &NewInvite {
+                    id: &invite_id,
+                    guild_id,
+                    creator_id,
+                    max_uses: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
             record_invite_use(&pool, &invite_id, user_id).await.unwrap();
         }
 
         // Test leaderboard
-        let entries = get_invite_leaderboard(&pool, guild_id, 30).await.unwrap();
+        let entries = get_invite_leaderboard(
+            &pool,
+            guild_id,
+            &LeaderboardQuery {
+                days: 30,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
         assert_eq!(entries.len(), 1);
         assert_eq!(entries[0].creator_id, creator_id);
         assert_eq!(entries[0].invite_count, 3);
     }
 
+    #[tokio::test]
+    async fn test_record_join_attribution() {
+        let pool = setup_test_db().await;
+
+        record_join_attribution(&pool, "123456789", "111222333", "ambiguous", "abc,def")
+            .await
+            .unwrap();
+
+        let (status, codes): (String, String) = sqlx::query_as(
+            "SELECT status, invite_codes FROM join_attributions WHERE user_id = '111222333'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(status, "ambiguous");
+        assert_eq!(codes, "abc,def");
+    }
+
     #[tokio::test]
     async fn test_mark_invite_used() {
         let pool = setup_test_db().await;
@@ -253,9 +1364,18 @@ mod tests {
         let user_id = "111222333";
 
         // Create invite
-        create_invite(&pool, &invite_id, guild_id, creator_id)
-            .await
-            .unwrap();
+        create_invite(
+            &pool,
+            &NewInvite {
+                id: &invite_id,
+                guild_id,
+                creator_id,
+                max_uses: 1,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
 
         // Mark as used
         record_invite_use(&pool, &invite_id, user_id).await.unwrap();
@@ -263,5 +1383,1221 @@ mod tests {
         // Verify invite is marked as used
         let invite = get_unused_invite(&pool, &invite_id).await.unwrap();
         assert!(invite.is_none());
+
+        // A second use of a single-use invite is ignored
+        record_invite_use(&pool, &invite_id, "444555666")
+            .await
+            .unwrap();
+        let count = count_used_invites(&pool, creator_id, guild_id, 30, Retention::All)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_invite() {
+        let pool = setup_test_db().await;
+        let invite_id = Uuid::new_v4().to_string();
+
+        create_invite(
+            &pool,
+            &NewInvite {
+                id: &invite_id,
+                guild_id: "123456789",
+                creator_id: "987654321",
+                max_uses: 1,
+                link_ttl: Some(3600),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        update_invite_code(&pool, &invite_id, "abc").await.unwrap();
+
+        let invite = get_invite(&pool, &invite_id).await.unwrap().unwrap();
+        assert!(invite.intended_for.is_none());
+        assert!(invite.grant_roles.is_none());
+        assert!(!invite.is_expired());
+        assert!(get_unused_invite(&pool, &invite_id)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(get_expired_invites(&pool).await.unwrap().is_empty());
+
+        // Move the expiry into the past
+        sqlx::query("UPDATE invites SET expires_at = datetime('now', '-1 minute')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let invite = get_invite(&pool, &invite_id).await.unwrap().unwrap();
+        assert!(invite.is_expired());
+        assert!(get_unused_invite(&pool, &invite_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let expired = get_expired_invites(&pool).await.unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].discord_invite_code, "abc");
+
+        mark_invite_swept(&pool, &invite_id).await.unwrap();
+        assert!(get_expired_invites(&pool).await.unwrap().is_empty());
+
+        // A used single-use link can't be redeemed again, so it keeps its code
+        let used_id = Uuid::new_v4().to_string();
+        create_invite(
+            &pool,
+            &NewInvite {
+                id: &used_id,
+                guild_id: "123456789",
+                creator_id: "987654321",
+                max_uses: 1,
+                link_ttl: Some(3600),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        update_invite_code(&pool, &used_id, "used").await.unwrap();
+        record_invite_use(&pool, &used_id, "222").await.unwrap();
+        sqlx::query("UPDATE invites SET expires_at = datetime('now', '-1 minute') WHERE id = ?")
+            .bind(&used_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(get_expired_invites(&pool).await.unwrap().is_empty());
+        let info = get_user_invite_info(&pool, "123456789", "222")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.discord_invite_code.as_deref(), Some("used"));
+
+        // A multi-use link that was partly used is swept all the same
+        let campaign_id = Uuid::new_v4().to_string();
+        create_invite(
+            &pool,
+            &NewInvite {
+                id: &campaign_id,
+                guild_id: "123456789",
+                creator_id: "987654321",
+                max_uses: 3,
+                link_ttl: Some(3600),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        update_invite_code(&pool, &campaign_id, "campaign")
+            .await
+            .unwrap();
+        record_invite_use(&pool, &campaign_id, "111").await.unwrap();
+        sqlx::query("UPDATE invites SET expires_at = datetime('now', '-1 minute') WHERE id = ?")
+            .bind(&campaign_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let expired = get_expired_invites(&pool).await.unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, campaign_id);
+
+        mark_invite_swept(&pool, &campaign_id).await.unwrap();
+        assert!(get_expired_invites(&pool).await.unwrap().is_empty());
+        let info = get_user_invite_info(&pool, "123456789", "111")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.discord_invite_code.as_deref(), Some("campaign"));
+    }
+
+    #[tokio::test]
+    async fn test_count_outstanding_invites() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+        let creator_id = "987654321";
+
+        let mut invite_ids = Vec::new();
+        for _ in 0..4 {
+            let invite_id = Uuid::new_v4().to_string();
+            create_invite(
+                &pool,
+                &NewInvite {
+                    id: &invite_id,
+                    guild_id,
+                    creator_id,
+                    max_uses: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            invite_ids.push(invite_id);
+        }
+        assert_eq!(
+            count_outstanding_invites(&pool, creator_id, guild_id)
+                .await
+                .unwrap(),
+            4
+        );
+
+        // Used and revoked links no longer count
+        record_invite_use(&pool, &invite_ids[0], "111222333")
+            .await
+            .unwrap();
+        revoke_invite(&pool, &invite_ids[1], creator_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            count_outstanding_invites(&pool, creator_id, guild_id)
+                .await
+                .unwrap(),
+            2
+        );
+
+        // A multi-use link counts until its last use is taken
+        let campaign_id = Uuid::new_v4().to_string();
+        create_invite(
+            &pool,
+            &NewInvite {
+                id: &campaign_id,
+                guild_id,
+                creator_id,
+                max_uses: 2,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        record_invite_use(&pool, &campaign_id, "444").await.unwrap();
+        assert_eq!(
+            count_outstanding_invites(&pool, creator_id, guild_id)
+                .await
+                .unwrap(),
+            3
+        );
+        record_invite_use(&pool, &campaign_id, "555").await.unwrap();
+        assert_eq!(
+            count_outstanding_invites(&pool, creator_id, guild_id)
+                .await
+                .unwrap(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_revoke_invite() {
+        let pool = setup_test_db().await;
+        let invite_id = Uuid::new_v4().to_string();
+
+        create_invite(
+            &pool,
+            &NewInvite {
+                id: &invite_id,
+                guild_id: "123456789",
+                creator_id: "987654321",
+                max_uses: 1,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(revoke_invite(&pool, &invite_id, "987654321").await.unwrap());
+        assert!(!revoke_invite(&pool, &invite_id, "987654321").await.unwrap());
+
+        // A revoked invite can no longer be redeemed
+        assert!(get_unused_invite(&pool, &invite_id)
+            .await
+            .unwrap()
+            .is_none());
+        let invite = get_invite(&pool, &invite_id).await.unwrap().unwrap();
+        assert!(invite.revoked_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_multi_use_invite() {
+        let pool = setup_test_db().await;
+        let invite_id = Uuid::new_v4().to_string();
+        let guild_id = "123456789";
+        let creator_id = "987654321";
+
+        create_invite(
+            &pool,
+            &NewInvite {
+                id: &invite_id,
+                guild_id,
+                creator_id,
+                max_uses: 3,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        update_invite_code(&pool, &invite_id, "campaign")
+            .await
+            .unwrap();
+
+        for user_id in ["111", "222"] {
+            let found = find_invite_by_code(&pool, "campaign").await.unwrap();
+            assert_eq!(found.as_deref(), Some(invite_id.as_str()));
+            record_invite_use(&pool, &invite_id, user_id).await.unwrap();
+        }
+
+        // Each use counts, and the link stays open until all uses are taken
+        let count = count_used_invites(&pool, creator_id, guild_id, 30, Retention::All)
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+        assert!(get_unused_invite(&pool, &invite_id)
+            .await
+            .unwrap()
+            .is_some());
+
+        record_invite_use(&pool, &invite_id, "333").await.unwrap();
+        assert!(get_unused_invite(&pool, &invite_id)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            count_invitees(&pool, guild_id, creator_id).await.unwrap(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invite_overrides() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+        let user_id = "111222333";
+
+        assert!(get_active_override(&pool, guild_id, user_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        set_override(
+            &pool,
+            &NewOverride {
+                guild_id,
+                user_id,
+                kind: "extra",
+                count: Some(3),
+                created_by: "987654321",
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // Setting again replaces the previous override
+        set_override(
+            &pool,
+            &NewOverride {
+                guild_id,
+                user_id,
+                kind: "deny",
+                created_by: "987654321",
+                duration_days: Some(7),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let active = get_active_override(&pool, guild_id, user_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(active.kind, "deny");
+        assert!(active.count.is_none());
+
+        // Expired overrides no longer apply
+        sqlx::query("UPDATE invite_overrides SET expires_at = datetime('now', '-1 minute')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(get_active_override(&pool, guild_id, user_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        assert!(delete_override(&pool, guild_id, user_id).await.unwrap());
+        assert!(!delete_override(&pool, guild_id, user_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_retention_modes() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+        let creator_id = "987654321";
+
+        for user_id in ["stayed", "left_early", "left_late"] {
+            let invite_id = Uuid::new_v4().to_string();
+            create_invite(
+                &pool,
+                &NewInvite {
+                    id: &invite_id,
+                    guild_id,
+                    creator_id,
+                    max_uses: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            record_invite_use(&pool, &invite_id, user_id).await.unwrap();
+        }
+
+        assert!(record_member_left(&pool, guild_id, "left_early")
+            .await
+            .unwrap());
+        assert!(record_member_left(&pool, guild_id, "left_late")
+            .await
+            .unwrap());
+        assert!(!record_member_left(&pool, guild_id, "left_late")
+            .await
+            .unwrap());
+
+        // Pretend the late leaver joined ten days before leaving
+        sqlx::query(
+            "UPDATE invites SET used_at = datetime('now', '-10 days') WHERE used_by = 'left_late'",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        for (retention, expected) in [
+            (Retention::All, 3),
+            (Retention::Present, 1),
+            (Retention::MinDays(7), 2),
+        ] {
+            let count = count_used_invites(&pool, creator_id, guild_id, 30, retention)
+                .await
+                .unwrap();
+            assert_eq!(count, expected, "{:?}", retention);
+
+            let entries = get_invite_leaderboard(
+                &pool,
+                guild_id,
+                &LeaderboardQuery {
+                    days: 30,
+                    retention,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            assert_eq!(entries[0].invite_count, expected, "{:?}", retention);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flagged_invitees() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+        let creator_id = "987654321";
+        let invite_id = Uuid::new_v4().to_string();
+
+        create_invite(
+            &pool,
+            &NewInvite {
+                id: &invite_id,
+                guild_id,
+                creator_id,
+                max_uses: 2,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(!has_joined_before(&pool, guild_id, "alt").await.unwrap());
+        record_join_attribution(&pool, guild_id, "alt", "matched", "abc")
+            .await
+            .unwrap();
+        assert!(has_joined_before(&pool, guild_id, "alt").await.unwrap());
+
+        // The second use is a child row, which must be flagged too
+        record_invite_use(&pool, &invite_id, "friend")
+            .await
+            .unwrap();
+        record_invite_use(&pool, &invite_id, "alt").await.unwrap();
+        flag_invite_use(&pool, &invite_id, "alt", "new_account")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            count_flagged_invitees(&pool, guild_id, creator_id)
+                .await
+                .unwrap(),
+            1
+        );
+
+        let entries = get_invite_leaderboard(
+            &pool,
+            guild_id,
+            &LeaderboardQuery {
+                days: 30,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(entries[0].invite_count, 2);
+        let entries = get_invite_leaderboard(
+            &pool,
+            guild_id,
+            &LeaderboardQuery {
+                days: 30,
+                exclude_flagged: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(entries[0].invite_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_ban_penalties() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+        let user_id = "111222333";
+        let active = |pool: Pool| async move {
+            get_active_override(&pool, guild_id, user_id)
+                .await
+                .unwrap()
+                .unwrap()
+        };
+
+        // Penalties stack, and reduce an existing grant
+        add_quota_penalty(&pool, guild_id, user_id, 1, "bot")
+            .await
+            .unwrap();
+        add_quota_penalty(&pool, guild_id, user_id, 2, "bot")
+            .await
+            .unwrap();
+        let current = active(pool.clone()).await;
+        assert_eq!((current.kind.as_str(), current.count), ("extra", Some(-3)));
+
+        // A suspension sits beside the penalty rather than replacing it
+        suspend_invites(&pool, guild_id, user_id, 7, "bot")
+            .await
+            .unwrap();
+        add_quota_penalty(&pool, guild_id, user_id, 1, "bot")
+            .await
+            .unwrap();
+        assert!(is_suspended(&pool, guild_id, user_id).await.unwrap());
+        let current = active(pool.clone()).await;
+        assert_eq!((current.kind.as_str(), current.count), ("extra", Some(-4)));
+
+        // A shorter suspension doesn't cut a longer one short
+        suspend_invites(&pool, guild_id, user_id, 30, "bot")
+            .await
+            .unwrap();
+        suspend_invites(&pool, guild_id, user_id, 1, "bot")
+            .await
+            .unwrap();
+        let kept: bool = sqlx::query_scalar(
+            "SELECT suspended_until > datetime('now', '+29 days') FROM invite_suspensions",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(kept);
+
+        // The penalty still applies once the suspension is over
+        sqlx::query("UPDATE invite_suspensions SET suspended_until = datetime('now', '-1 minute')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(!is_suspended(&pool, guild_id, user_id).await.unwrap());
+        assert!(!lift_suspension(&pool, guild_id, user_id).await.unwrap());
+        let current = active(pool.clone()).await;
+        assert_eq!((current.kind.as_str(), current.count), ("extra", Some(-4)));
+
+        // An expired override is replaced by the penalty
+        sqlx::query("UPDATE invite_overrides SET expires_at = datetime('now', '-1 minute')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        add_quota_penalty(&pool, guild_id, user_id, 1, "bot")
+            .await
+            .unwrap();
+        let current = active(pool.clone()).await;
+        assert_eq!((current.kind.as_str(), current.count), ("extra", Some(-1)));
+    }
+
+    #[tokio::test]
+    async fn test_get_inviter() {
+        let pool = setup_test_db().await;
+
+        for (guild_id, creator_id) in [("1", "alice"), ("2", "bob")] {
+            let invite_id = Uuid::new_v4().to_string();
+            create_invite(
+                &pool,
+                &NewInvite {
+                    id: &invite_id,
+                    guild_id,
+                    creator_id,
+                    max_uses: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            record_invite_use(&pool, &invite_id, "carol").await.unwrap();
+        }
+
+        // A join in another guild must not blame that guild's inviter
+        assert_eq!(
+            get_inviter(&pool, "1", "carol").await.unwrap().as_deref(),
+            Some("alice")
+        );
+        assert_eq!(get_inviter(&pool, "3", "carol").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_invite_lookup_is_guild_scoped() {
+        let pool = setup_test_db().await;
+
+        for (guild_id, creator_id) in [("1", "alice"), ("2", "bob")] {
+            let invite_id = Uuid::new_v4().to_string();
+            create_invite(
+                &pool,
+                &NewInvite {
+                    id: &invite_id,
+                    guild_id,
+                    creator_id,
+                    max_uses: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            record_invite_use(&pool, &invite_id, "carol").await.unwrap();
+        }
+
+        let info = get_user_invite_info(&pool, "1", "carol")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.creator_id.as_deref(), Some("alice"));
+        let info = get_user_invite_info(&pool, "2", "carol")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.creator_id.as_deref(), Some("bob"));
+        assert!(get_user_invite_info(&pool, "3", "carol")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_invitee_tree() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+
+        // alice -> bob -> carol -> alice (rejoined), and alice -> dave
+        for (creator_id, user_id) in [
+            ("alice", "bob"),
+            ("bob", "carol"),
+            ("carol", "alice"),
+            ("alice", "dave"),
+        ] {
+            let invite_id = Uuid::new_v4().to_string();
+            create_invite(
+                &pool,
+                &NewInvite {
+                    id: &invite_id,
+                    guild_id,
+                    creator_id,
+                    max_uses: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            record_invite_use(&pool, &invite_id, user_id).await.unwrap();
+        }
+
+        let tree = get_invitee_tree(&pool, guild_id, "alice", 10)
+            .await
+            .unwrap();
+        let entries: Vec<(&str, &str, i64)> = tree
+            .iter()
+            .map(|e| (e.user_id.as_str(), e.inviter_id.as_str(), e.depth))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("bob", "alice", 1),
+                ("dave", "alice", 1),
+                ("carol", "bob", 2)
+            ]
+        );
+
+        let tree = get_invitee_tree(&pool, guild_id, "alice", 1).await.unwrap();
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_leaderboard_metrics() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+
+        // alice: 3 links, 2 used, 1 of whom left; bob: 1 link, used
+        for (creator_id, user_id) in [
+            ("alice", Some("a1")),
+            ("alice", Some("a2")),
+            ("alice", None),
+            ("bob", Some("b1")),
+        ] {
+            let invite_id = Uuid::new_v4().to_string();
+            create_invite(
+                &pool,
+                &NewInvite {
+                    id: &invite_id,
+                    guild_id,
+                    creator_id,
+                    max_uses: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            if let Some(user_id) = user_id {
+                record_invite_use(&pool, &invite_id, user_id).await.unwrap();
+            }
+        }
+        record_member_left(&pool, guild_id, "a2").await.unwrap();
+
+        for (metric, expected) in [
+            (LeaderboardMetric::Used, vec![("alice", 2), ("bob", 1)]),
+            (LeaderboardMetric::Retained, vec![("alice", 1), ("bob", 1)]),
+            (LeaderboardMetric::Created, vec![("alice", 3), ("bob", 1)]),
+        ] {
+            let entries = get_invite_leaderboard(
+                &pool,
+                guild_id,
+                &LeaderboardQuery {
+                    days: 30,
+                    metric,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            let entries: Vec<(&str, i64)> = entries
+                .iter()
+                .map(|e| (e.creator_id.as_str(), e.invite_count))
+                .collect();
+            assert_eq!(entries, expected, "{:?}", metric);
+        }
+
+        // Under the retained count mode, invitees who stayed long enough count as retained
+        sqlx::query(
+            "UPDATE invites SET used_at = datetime('now', '-10 days') WHERE used_by = 'a2'",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let entries = get_invite_leaderboard(
+            &pool,
+            guild_id,
+            &LeaderboardQuery {
+                days: 30,
+                metric: LeaderboardMetric::Retained,
+                retention: Retention::MinDays(7),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(entries[0].creator_id, "alice");
+        assert_eq!(entries[0].invite_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_invite_stats() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+        let creator_id = "987654321";
+
+        let mut invite_ids = Vec::new();
+        for _ in 0..5 {
+            let invite_id = Uuid::new_v4().to_string();
+            create_invite(
+                &pool,
+                &NewInvite {
+                    id: &invite_id,
+                    guild_id,
+                    creator_id,
+                    max_uses: 2,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            invite_ids.push(invite_id);
+        }
+
+        // One link used twice, one used once by someone who left
+        record_invite_use(&pool, &invite_ids[0], "111")
+            .await
+            .unwrap();
+        record_invite_use(&pool, &invite_ids[0], "222")
+            .await
+            .unwrap();
+        record_invite_use(&pool, &invite_ids[1], "333")
+            .await
+            .unwrap();
+        record_member_left(&pool, guild_id, "333").await.unwrap();
+        revoke_invite(&pool, &invite_ids[2], creator_id)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE invites SET expires_at = datetime('now', '-1 minute') WHERE id = ?")
+            .bind(&invite_ids[3])
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let stats = get_invite_stats(&pool, guild_id, creator_id).await.unwrap();
+        assert_eq!(
+            stats,
+            InviteStats {
+                created: 5,
+                used: 2,
+                pending: 1,
+                expired: 1,
+                revoked: 1,
+                retained: 2,
+            }
+        );
+
+        let recent = get_recent_invitees(&pool, guild_id, creator_id, 2)
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 2);
+        assert!(recent.iter().any(|invitee| invitee.left_at.is_some()));
+
+        // Nothing recorded yet
+        assert_eq!(
+            get_invite_stats(&pool, "other", creator_id).await.unwrap(),
+            InviteStats::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_invites() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+
+        let mut invite_ids = Vec::new();
+        for (index, creator_id) in ["alice", "alice", "bob", "bob"].iter().enumerate() {
+            let invite_id = format!("invite-{}", index);
+            create_invite(
+                &pool,
+                &NewInvite {
+                    id: &invite_id,
+                    guild_id,
+                    creator_id,
+                    max_uses: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            invite_ids.push(invite_id);
+        }
+        // Spread creation over the past days, newest last
+        for (index, invite_id) in invite_ids.iter().enumerate() {
+            sqlx::query("UPDATE invites SET created_at = datetime('now', ?) WHERE id = ?")
+                .bind(format!("-{} days", 10 - index))
+                .bind(invite_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        record_invite_use(&pool, &invite_ids[0], "111")
+            .await
+            .unwrap();
+        revoke_invite(&pool, &invite_ids[2], "bob").await.unwrap();
+
+        let ids =
+            |entries: Vec<InviteListEntry>| entries.into_iter().map(|e| e.id).collect::<Vec<_>>();
+
+        let all = list_invites(&pool, &InviteFilter::default(), 10)
+            .await
+            .unwrap();
+        assert_eq!(all[0].id, "invite-3");
+        assert_eq!(all[3].status, "used");
+        assert_eq!(all[3].uses, 1);
+
+        let filtered = list_invites(
+            &pool,
+            &InviteFilter {
+                creator_id: Some("bob"),
+                status: Some(InviteStatus::Pending),
+                ..Default::default()
+            },
+            10,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ids(filtered), vec!["invite-3"]);
+
+        // Cursor pagination walks the same order without repeats
+        let first = list_invites(&pool, &InviteFilter::default(), 2)
+            .await
+            .unwrap();
+        let second = list_invites(
+            &pool,
+            &InviteFilter {
+                after: Some(&first[1].id),
+                ..Default::default()
+            },
+            2,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ids(first), vec!["invite-3", "invite-2"]);
+        assert_eq!(ids(second), vec!["invite-1", "invite-0"]);
+
+        // The range includes both end days
+        let today = chrono::Utc::now().date_naive();
+        let ranged = list_invites(
+            &pool,
+            &InviteFilter {
+                created_from: Some(today - chrono::Duration::days(9)),
+                created_to: Some(today - chrono::Duration::days(8)),
+                ..Default::default()
+            },
+            10,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ids(ranged), vec!["invite-2", "invite-1"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_invite_history() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+
+        create_invite(
+            &pool,
+            &NewInvite {
+                id: "campaign",
+                guild_id,
+                creator_id: "alice",
+                max_uses: 5,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        create_invite(
+            &pool,
+            &NewInvite {
+                id: "single",
+                guild_id,
+                creator_id: "bob",
+                max_uses: 1,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        update_invite_code(&pool, "campaign", "abc").await.unwrap();
+        record_invite_use(&pool, "campaign", "111").await.unwrap();
+        record_invite_use(&pool, "campaign", "222").await.unwrap();
+        record_member_left(&pool, guild_id, "222").await.unwrap();
+
+        let history = get_invite_history(&pool, guild_id, None, None)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 3);
+
+        let uses: Vec<_> = history
+            .iter()
+            .filter(|entry| entry.invite_id == "campaign")
+            .collect();
+        assert_eq!(uses.len(), 2);
+        assert!(uses
+            .iter()
+            .all(|entry| entry.discord_invite_code.as_deref() == Some("abc")));
+        let left = uses
+            .iter()
+            .find(|entry| entry.invitee_id.as_deref() == Some("222"))
+            .unwrap();
+        assert!(left.left_at.is_some());
+
+        let single = history.iter().find(|e| e.invite_id == "single").unwrap();
+        assert!(single.invitee_id.is_none());
+
+        // Nothing happened before yesterday
+        let yesterday = chrono::Utc::now().date_naive() - chrono::Duration::days(1);
+        let history = get_invite_history(&pool, guild_id, None, Some(yesterday))
+            .await
+            .unwrap();
+        assert!(history.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_invite() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+        let imported = ImportedInvite {
+            guild_id: guild_id.to_string(),
+            inviter_id: "alice".to_string(),
+            invitee_id: "111".to_string(),
+            joined_at: chrono::Utc::now().naive_utc() - chrono::Duration::days(3),
+        };
+
+        assert!(import_invite(&pool, &imported).await.unwrap());
+        // Importing the same file twice adds nothing
+        assert!(!import_invite(&pool, &imported).await.unwrap());
+
+        let info = get_user_invite_info(&pool, guild_id, "111")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.creator_id.as_deref(), Some("alice"));
+
+        // Exports tell imported history apart from the bot's own links
+        let history = get_invite_history(&pool, guild_id, None, None)
+            .await
+            .unwrap();
+        assert_eq!(history[0].source, "imported");
+
+        // Imported joins don't use up quota
+        let used = count_used_invites(&pool, "alice", guild_id, 30, Retention::All)
+            .await
+            .unwrap();
+        assert_eq!(used, 0);
+
+        let mut query = LeaderboardQuery {
+            days: 30,
+            ..Default::default()
+        };
+        let leaderboard = get_invite_leaderboard(&pool, guild_id, &query)
+            .await
+            .unwrap();
+        assert!(leaderboard.is_empty());
+
+        query.include_imported = true;
+        let leaderboard = get_invite_leaderboard(&pool, guild_id, &query)
+            .await
+            .unwrap();
+        assert_eq!(leaderboard[0].invite_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_leaderboard_offset() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+
+        // alice invited someone 3 days ago, bob 10 days ago
+        for (creator_id, days_ago) in [("alice", 3), ("bob", 10)] {
+            let invite_id = format!("{}-invite", creator_id);
+            create_invite(
+                &pool,
+                &NewInvite {
+                    id: &invite_id,
+                    guild_id,
+                    creator_id,
+                    max_uses: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            record_invite_use(&pool, &invite_id, "111").await.unwrap();
+            sqlx::query("UPDATE invites SET created_at = datetime('now', ?) WHERE id = ?")
+                .bind(format!("-{} days", days_ago))
+                .bind(&invite_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let mut query = LeaderboardQuery {
+            days: 7,
+            ..Default::default()
+        };
+        let current = get_invite_leaderboard(&pool, guild_id, &query)
+            .await
+            .unwrap();
+        assert_eq!(current.len(), 1);
+        assert_eq!(current[0].creator_id, "alice");
+
+        query.offset_days = 7;
+        let previous = get_invite_leaderboard(&pool, guild_id, &query)
+            .await
+            .unwrap();
+        assert_eq!(previous.len(), 1);
+        assert_eq!(previous[0].creator_id, "bob");
+    }
+
+    #[tokio::test]
+    async fn test_last_announcement() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+        assert!(get_last_announcement(&pool, guild_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let posted_at = chrono::NaiveDate::from_ymd_opt(2025, 1, 6)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        set_last_announcement(&pool, guild_id, posted_at)
+            .await
+            .unwrap();
+        set_last_announcement(&pool, guild_id, posted_at + chrono::Duration::days(7))
+            .await
+            .unwrap();
+        assert_eq!(
+            get_last_announcement(&pool, guild_id).await.unwrap(),
+            Some(posted_at + chrono::Duration::days(7))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_review_invite() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+        for invite_id in ["approved", "denied"] {
+            create_invite(
+                &pool,
+                &NewInvite {
+                    id: invite_id,
+                    guild_id,
+                    creator_id: "alice",
+                    max_uses: 1,
+                    pending_approval: true,
+                    approval_note: Some("my friend bob"),
+                    link_ttl: Some(3600),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            // Not redeemable until reviewed
+            assert!(get_unused_invite(&pool, invite_id).await.unwrap().is_none());
+            let details = get_invite(&pool, invite_id).await.unwrap().unwrap();
+            assert!(details.is_awaiting_approval());
+        }
+
+        // The review took longer than the link's lifetime, which starts over on approval
+        sqlx::query(
+            "UPDATE invites SET created_at = datetime('now', '-2 hours'),
+             expires_at = datetime('now', '-1 hour')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let reviewed = review_invite(&pool, "approved", true, "mod")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reviewed.creator_id, "alice");
+        assert_eq!(reviewed.approval_note.as_deref(), Some("my friend bob"));
+        assert!(get_unused_invite(&pool, "approved")
+            .await
+            .unwrap()
+            .is_some());
+        let ttl: i64 = sqlx::query_scalar(
+            "SELECT strftime('%s', expires_at) - strftime('%s', 'now') FROM invites
+             WHERE id = 'approved'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!((3590..=3600).contains(&ttl), "{}", ttl);
+        // A second click does nothing
+        assert!(review_invite(&pool, "approved", false, "mod")
+            .await
+            .unwrap()
+            .is_none());
+
+        review_invite(&pool, "denied", false, "mod")
+            .await
+            .unwrap()
+            .unwrap();
+        let details = get_invite(&pool, "denied").await.unwrap().unwrap();
+        assert!(details.revoked_at.is_some());
+        assert!(!details.is_awaiting_approval());
+        assert_eq!(
+            count_outstanding_invites(&pool, "alice", guild_id)
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_count_reward_invitees() {
+        let pool = setup_test_db().await;
+        let guild_id = "123456789";
+        let creator_id = "987654321";
+
+        for user_id in ["recent", "old", "left"] {
+            let invite_id = Uuid::new_v4().to_string();
+            create_invite(
+                &pool,
+                &NewInvite {
+                    id: &invite_id,
+                    guild_id,
+                    creator_id,
+                    max_uses: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            record_invite_use(&pool, &invite_id, user_id).await.unwrap();
+        }
+        record_member_left(&pool, guild_id, "left").await.unwrap();
+        sqlx::query(
+            "UPDATE invites SET used_at = datetime('now', '-40 days') WHERE used_by = 'old'",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // The window is on the join, not on when the link was made
+        for (days, retention, expected) in [
+            (None, Retention::All, 3),
+            (Some(30), Retention::All, 2),
+            (None, Retention::MinDays(7), 2),
+            (Some(30), Retention::MinDays(7), 1),
+        ] {
+            let count = count_reward_invitees(&pool, guild_id, creator_id, days, retention)
+                .await
+                .unwrap();
+            assert_eq!(count, expected, "{:?} {:?}", days, retention);
+        }
+
+        // Unused links don't make someone an inviter
+        create_invite(
+            &pool,
+            &NewInvite {
+                id: "unused",
+                guild_id,
+                creator_id: "alice",
+                max_uses: 1,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            get_reward_inviters(&pool, guild_id).await.unwrap(),
+            vec![creator_id.to_string()]
+        );
     }
 }
@@ -0,0 +1,125 @@
+use crate::utils::db::InviteHistoryEntry;
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+
+const CSV_HEADER: &str =
+    "invite_id,creator_id,invitee_id,created_at,used_at,left_at,discord_invite_code,source";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json => "application/json",
+        }
+    }
+
+    pub fn render(&self, entries: &[InviteHistoryEntry]) -> Result<String, serde_json::Error> {
+        match self {
+            ExportFormat::Csv => Ok(to_csv(entries)),
+            ExportFormat::Json => serde_json::to_string_pretty(entries),
+        }
+    }
+}
+
+/// `invites-<guild>-<date>.<ext>`, the name of the file a guild's export is saved as.
+pub fn file_name(guild_id: &str, format: ExportFormat) -> String {
+    format!(
+        "invites-{}-{}.{}",
+        guild_id,
+        chrono::Utc::now().format("%Y-%m-%d"),
+        format.extension()
+    )
+}
+
+fn to_csv(entries: &[InviteHistoryEntry]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for entry in entries {
+        let fields = [
+            entry.invite_id.clone(),
+            entry.creator_id.clone(),
+            entry.invitee_id.clone().unwrap_or_default(),
+            timestamp(Some(entry.created_at)),
+            timestamp(entry.used_at),
+            timestamp(entry.left_at),
+            entry.discord_invite_code.clone().unwrap_or_default(),
+            entry.source.clone(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| escape(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Same form as the JSON export, so both load the same way in a spreadsheet.
+fn timestamp(value: Option<NaiveDateTime>) -> String {
+    value
+        .map(|value| value.format("%Y-%m-%dT%H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry() -> InviteHistoryEntry {
+        InviteHistoryEntry {
+            invite_id: "abc".to_string(),
+            creator_id: "111".to_string(),
+            invitee_id: Some("222".to_string()),
+            created_at: chrono::NaiveDate::from_ymd_opt(2025, 1, 31)
+                .unwrap()
+                .and_hms_opt(12, 30, 0)
+                .unwrap(),
+            used_at: None,
+            left_at: None,
+            discord_invite_code: Some("x,y\"z".to_string()),
+            source: "bot".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_csv() {
+        let csv = ExportFormat::Csv.render(&[entry()]).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], CSV_HEADER);
+        // 空欄位留白，含逗號或引號的欄位加上引號
+        assert_eq!(
+            lines[1],
+            "abc,111,222,2025-01-31T12:30:00,,,\"x,y\"\"z\",bot"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let json = ExportFormat::Json.render(&[entry()]).unwrap();
+
+        assert!(json.contains("\"created_at\": \"2025-01-31T12:30:00\""));
+        assert!(json.contains("\"used_at\": null"));
+    }
+}
@@ -43,11 +43,7 @@ pub fn get_text(locale: &str, key: &str, params: Option<HashMap<&str, String>>)
 
         if let Some(text) = value.as_str() {
             if let Some(params) = &params {
-                let mut result = text.to_string();
-                for (key, value) in params {
-                    result = result.replace(&format!("{{{}}}", key), value);
-                }
-                return result;
+                return fill(text, params);
             }
             return text.to_string();
         }
@@ -56,6 +52,15 @@ pub fn get_text(locale: &str, key: &str, params: Option<HashMap<&str, String>>)
     key.to_string()
 }
 
+/// Replaces the `{name}` placeholders of a template, such as a guild's own message.
+pub fn fill(template: &str, params: &HashMap<&str, String>) -> String {
+    let mut result = template.to_string();
+    for (key, value) in params {
+        result = result.replace(&format!("{{{}}}", key), value);
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,225 @@
+use crate::utils::db::ImportedInvite;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+use std::fmt;
+use std::num::NonZeroU64;
+
+const COLUMNS: [&str; 4] = ["guild_id", "inviter_id", "invitee_id", "joined_at"];
+
+#[derive(Debug)]
+pub enum ImportError {
+    Json(serde_json::Error),
+    /// The CSV header lacks one of the required columns.
+    MissingColumn(&'static str),
+    /// A row that couldn't be read, by 1-based line number (or index for JSON).
+    InvalidRow(usize, String),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Json(e) => write!(f, "invalid JSON: {}", e),
+            ImportError::MissingColumn(column) => write!(f, "missing column {}", column),
+            ImportError::InvalidRow(row, reason) => write!(f, "row {}: {}", row, reason),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<serde_json::Error> for ImportError {
+    fn from(e: serde_json::Error) -> Self {
+        ImportError::Json(e)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportRow {
+    guild_id: String,
+    inviter_id: String,
+    invitee_id: String,
+    joined_at: String,
+}
+
+impl ImportRow {
+    fn into_invite(self, row: usize) -> Result<ImportedInvite, ImportError> {
+        // Bot code turns these into Discord IDs later, which can't be 0
+        let id = |column: &str, value: &str| {
+            value
+                .parse::<NonZeroU64>()
+                .map(|id| id.to_string())
+                .map_err(|_| {
+                    ImportError::InvalidRow(row, format!("invalid {} {:?}", column, value))
+                })
+        };
+        let joined_at = parse_timestamp(&self.joined_at).ok_or_else(|| {
+            ImportError::InvalidRow(row, format!("invalid joined_at {:?}", self.joined_at))
+        })?;
+        Ok(ImportedInvite {
+            guild_id: id("guild_id", &self.guild_id)?,
+            inviter_id: id("inviter_id", &self.inviter_id)?,
+            invitee_id: id("invitee_id", &self.invitee_id)?,
+            joined_at,
+        })
+    }
+}
+
+/// Reads a JSON array of `{guild_id, inviter_id, invitee_id, joined_at}` objects.
+pub fn from_json(input: &str) -> Result<Vec<ImportedInvite>, ImportError> {
+    let rows: Vec<ImportRow> = serde_json::from_str(input)?;
+    rows.into_iter()
+        .enumerate()
+        .map(|(index, row)| row.into_invite(index + 1))
+        .collect()
+}
+
+/// Reads a CSV file whose header names the `guild_id`, `inviter_id`, `invitee_id`
+/// and `joined_at` columns, in any order.
+pub fn from_csv(input: &str) -> Result<Vec<ImportedInvite>, ImportError> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let header = lines
+        .next()
+        .map(|(_, line)| split_line(line))
+        .unwrap_or_default();
+    let mut indices = [0; COLUMNS.len()];
+    for (index, column) in indices.iter_mut().zip(COLUMNS) {
+        *index = header
+            .iter()
+            .position(|name| name.trim() == column)
+            .ok_or(ImportError::MissingColumn(column))?;
+    }
+
+    lines
+        .map(|(number, line)| {
+            let fields = split_line(line);
+            let field = |index: usize| {
+                fields
+                    .get(indices[index])
+                    .map(|field| field.trim().to_string())
+                    .filter(|field| !field.is_empty())
+                    .ok_or_else(|| {
+                        ImportError::InvalidRow(number + 1, format!("missing {}", COLUMNS[index]))
+                    })
+            };
+            ImportRow {
+                guild_id: field(0)?,
+                inviter_id: field(1)?,
+                invitee_id: field(2)?,
+                joined_at: field(3)?,
+            }
+            .into_invite(number + 1)
+        })
+        .collect()
+}
+
+/// Splits a CSV line, honouring double-quoted fields with `""` escapes.
+fn split_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Accepts RFC 3339, `YYYY-MM-DD HH:MM:SS` (with `T` or a space) in UTC, or a bare date.
+fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.naive_utc())
+        .ok()
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").ok())
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let input = "invitee_id,guild_id,inviter_id,joined_at\n\
+                     111,123,456,2024-05-01T12:00:00+08:00\n\
+                     \n\
+                     \"222\",123,456,2024-05-02\n";
+        let invites = from_csv(input).unwrap();
+
+        // 欄位順序不拘，時區換算成 UTC
+        assert_eq!(invites.len(), 2);
+        assert_eq!(invites[0].invitee_id, "111");
+        assert_eq!(invites[0].inviter_id, "456");
+        assert_eq!(invites[0].joined_at, timestamp("2024-05-01 04:00:00"));
+        assert_eq!(invites[1].invitee_id, "222");
+        assert_eq!(invites[1].joined_at, timestamp("2024-05-02 00:00:00"));
+    }
+
+    #[test]
+    fn test_from_csv_errors() {
+        assert!(matches!(
+            from_csv("guild_id,inviter_id,joined_at\n"),
+            Err(ImportError::MissingColumn("invitee_id"))
+        ));
+        assert!(matches!(
+            from_csv("guild_id,inviter_id,invitee_id,joined_at\n123,456,111,yesterday\n"),
+            Err(ImportError::InvalidRow(2, _))
+        ));
+        assert!(matches!(
+            from_csv("guild_id,inviter_id,invitee_id,joined_at\n123,456,,2024-05-01\n"),
+            Err(ImportError::InvalidRow(2, _))
+        ));
+        // ID 必須是非零的數字
+        assert!(matches!(
+            from_csv("guild_id,inviter_id,invitee_id,joined_at\n123,abc,111,2024-05-01\n"),
+            Err(ImportError::InvalidRow(2, _))
+        ));
+        assert!(matches!(
+            from_csv("guild_id,inviter_id,invitee_id,joined_at\n123,456,111,2024-05-01\n0,456,222,2024-05-01\n"),
+            Err(ImportError::InvalidRow(3, _))
+        ));
+    }
+
+    #[test]
+    fn test_from_json() {
+        let input = r#"[
+            {"guild_id": "123", "inviter_id": "456", "invitee_id": "111", "joined_at": "2024-05-01 12:00:00"}
+        ]"#;
+        let invites = from_json(input).unwrap();
+
+        assert_eq!(invites.len(), 1);
+        assert_eq!(invites[0].joined_at, timestamp("2024-05-01 12:00:00"));
+        assert!(from_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_split_line() {
+        assert_eq!(
+            split_line("a,\"b,c\",\"d\"\"e\","),
+            ["a", "b,c", "d\"e", ""]
+        );
+    }
+}
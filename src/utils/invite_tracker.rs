@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// How long after an invite delete event a vanished code may still be claimed by a join.
+///
+/// Discord deletes an exhausted invite and announces the member in separate events,
+/// with no guaranteed order between them.
+pub const DELETE_GRACE_SECS: i64 = 30;
+
+/// What we know about one invite code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedInvite {
+    pub uses: u64,
+    /// 0 means unlimited.
+    pub max_uses: u64,
+    /// Unix timestamp, `None` if the invite never expires.
+    pub expires_at: Option<i64>,
+    /// Unix timestamp of the invite delete event, if one was received.
+    pub deleted_at: Option<i64>,
+}
+
+impl TrackedInvite {
+    pub fn new(uses: u64, max_uses: u64, created_at: i64, max_age: u32) -> Self {
+        Self {
+            uses,
+            max_uses,
+            expires_at: (max_age > 0).then(|| created_at + max_age as i64),
+            deleted_at: None,
+        }
+    }
+
+    pub fn from_rich(invite: &poise::serenity_prelude::RichInvite) -> Self {
+        Self::new(
+            invite.uses,
+            invite.max_uses as u64,
+            invite.created_at.unix_timestamp(),
+            invite.max_age,
+        )
+    }
+
+    /// Whether this code disappearing at `now` is explained by its last use.
+    fn vanished_by_use(&self, now: i64) -> bool {
+        self.max_uses > 0
+            && self.uses + 1 >= self.max_uses
+            && self.expires_at.is_none_or(|expires_at| expires_at > now)
+            && self
+                .deleted_at
+                .is_none_or(|deleted_at| now - deleted_at <= DELETE_GRACE_SECS)
+    }
+}
+
+/// Known invites of a guild, keyed by code.
+pub type Snapshot = HashMap<String, TrackedInvite>;
+
+/// Which invite a new member came through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Attribution {
+    /// Exactly one code was consumed since the last snapshot.
+    Matched(String),
+    /// Several codes were consumed at once, so the join can't be pinned to one.
+    Ambiguous(Vec<String>),
+    /// No consumed code was found, or the guild had no snapshot yet.
+    Unknown,
+}
+
+impl Attribution {
+    pub fn status(&self) -> &'static str {
+        match self {
+            Attribution::Matched(_) => "matched",
+            Attribution::Ambiguous(_) => "ambiguous",
+            Attribution::Unknown => "unknown",
+        }
+    }
+
+    pub fn codes(&self) -> Vec<String> {
+        match self {
+            Attribution::Matched(code) => vec![code.clone()],
+            Attribution::Ambiguous(codes) => codes.clone(),
+            Attribution::Unknown => vec![],
+        }
+    }
+}
+
+/// In-memory cache of invite use counts per guild.
+///
+/// Seeded on ready and kept current by invite create/delete events, so a join can
+/// be attributed by diffing the live invite list against the last known state.
+/// Single-use invites are gone from the live list once redeemed, so a tracked code
+/// that vanishes on its last use counts as consumed too.
+#[derive(Debug, Default)]
+pub struct InviteTracker {
+    snapshots: Mutex<HashMap<String, Snapshot>>,
+    /// One lock per guild, taken by joins for the whole fetch and diff.
+    guild_locks: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+impl InviteTracker {
+    /// Hold across fetching a guild's live invites and [`Self::attribute`], so a
+    /// join never diffs against a snapshot another join is about to replace.
+    pub async fn lock_guild(&self, guild_id: &str) -> OwnedMutexGuard<()> {
+        let lock = self
+            .guild_locks
+            .lock()
+            .await
+            .entry(guild_id.to_string())
+            .or_default()
+            .clone();
+        lock.lock_owned().await
+    }
+
+    pub async fn seed(&self, guild_id: &str, invites: Snapshot) {
+        self.snapshots
+            .lock()
+            .await
+            .insert(guild_id.to_string(), invites);
+    }
+
+    pub async fn insert(&self, guild_id: &str, code: &str, invite: TrackedInvite) {
+        let mut snapshots = self.snapshots.lock().await;
+        if let Some(snapshot) = snapshots.get_mut(guild_id) {
+            snapshot.insert(code.to_string(), invite);
+        }
+    }
+
+    /// Notes a delete event, keeping the code around in case a join claims it.
+    pub async fn mark_deleted(&self, guild_id: &str, code: &str, now: i64) {
+        let mut snapshots = self.snapshots.lock().await;
+        if let Some(invite) = snapshots
+            .get_mut(guild_id)
+            .and_then(|snapshot| snapshot.get_mut(code))
+        {
+            invite.deleted_at = Some(now);
+        }
+    }
+
+    /// Forgets a code outright, for invites the bot deletes itself.
+    pub async fn remove(&self, guild_id: &str, code: &str) {
+        let mut snapshots = self.snapshots.lock().await;
+        if let Some(snapshot) = snapshots.get_mut(guild_id) {
+            snapshot.remove(code);
+        }
+    }
+
+    /// Diffs `live` against the cached snapshot, then makes `live` the new snapshot.
+    pub async fn attribute(&self, guild_id: &str, live: Snapshot, now: i64) -> Attribution {
+        let mut snapshots = self.snapshots.lock().await;
+        let attribution = match snapshots.get(guild_id) {
+            Some(cached) => diff(cached, &live, now),
+            None => Attribution::Unknown,
+        };
+        snapshots.insert(guild_id.to_string(), live);
+        attribution
+    }
+}
+
+pub fn diff(cached: &Snapshot, live: &Snapshot, now: i64) -> Attribution {
+    let increased = live.iter().filter(|(code, invite)| {
+        invite.uses > cached.get(*code).map(|cached| cached.uses).unwrap_or(0)
+    });
+    let vanished = cached
+        .iter()
+        .filter(|(code, invite)| !live.contains_key(*code) && invite.vanished_by_use(now));
+
+    let mut used: Vec<String> = increased
+        .chain(vanished)
+        .map(|(code, _)| code.clone())
+        .collect();
+    used.sort();
+
+    match used.len() {
+        0 => Attribution::Unknown,
+        1 => Attribution::Matched(used.remove(0)),
+        _ => Attribution::Ambiguous(used),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn snapshot(entries: &[(&str, u64, u64)]) -> Snapshot {
+        entries
+            .iter()
+            .map(|(code, uses, max_uses)| {
+                (
+                    code.to_string(),
+                    TrackedInvite::new(*uses, *max_uses, NOW - 60, 300),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let cached = snapshot(&[("a", 0, 0), ("b", 1, 0)]);
+
+        assert_eq!(
+            diff(&cached, &snapshot(&[("a", 1, 0), ("b", 1, 0)]), NOW),
+            Attribution::Matched("a".to_string())
+        );
+        assert_eq!(
+            diff(&cached, &snapshot(&[("a", 1, 0), ("b", 2, 0)]), NOW),
+            Attribution::Ambiguous(vec!["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(
+            diff(&cached, &snapshot(&[("a", 0, 0), ("b", 1, 0)]), NOW),
+            Attribution::Unknown
+        );
+
+        // 快照之後才建立的邀請
+        assert_eq!(
+            diff(
+                &cached,
+                &snapshot(&[("a", 0, 0), ("b", 1, 0), ("c", 1, 0)]),
+                NOW
+            ),
+            Attribution::Matched("c".to_string())
+        );
+    }
+
+    #[test]
+    fn test_diff_single_use_vanished() {
+        let cached = snapshot(&[("a", 0, 1), ("b", 0, 0)]);
+        let live = snapshot(&[("b", 0, 0)]);
+
+        // 單次邀請被使用後會從列表消失
+        assert_eq!(
+            diff(&cached, &live, NOW),
+            Attribution::Matched("a".to_string())
+        );
+
+        // 已過期的邀請消失不代表被使用
+        assert_eq!(diff(&cached, &live, NOW + 600), Attribution::Unknown);
+
+        // 刪除事件太久以前的邀請也不算
+        let mut deleted = cached.clone();
+        deleted.get_mut("a").unwrap().deleted_at = Some(NOW - DELETE_GRACE_SECS - 1);
+        assert_eq!(diff(&deleted, &live, NOW), Attribution::Unknown);
+
+        // 無限次數的邀請消失是被手動刪除
+        let cached = snapshot(&[("b", 0, 0)]);
+        assert_eq!(diff(&cached, &Snapshot::new(), NOW), Attribution::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_attribute_updates_snapshot() {
+        let tracker = InviteTracker::default();
+
+        // 尚未建立快照的公會無法判斷
+        assert_eq!(
+            tracker.attribute("1", snapshot(&[("a", 1, 0)]), NOW).await,
+            Attribution::Unknown
+        );
+
+        tracker
+            .insert("1", "b", TrackedInvite::new(0, 1, NOW, 300))
+            .await;
+        tracker.mark_deleted("1", "b", NOW).await;
+        assert_eq!(
+            tracker.attribute("1", snapshot(&[("a", 1, 0)]), NOW).await,
+            Attribution::Matched("b".to_string())
+        );
+
+        // 同一次使用不會被重複計算
+        assert_eq!(
+            tracker.attribute("1", snapshot(&[("a", 1, 0)]), NOW).await,
+            Attribution::Unknown
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lock_guild() {
+        let tracker = InviteTracker::default();
+        let wait = std::time::Duration::from_millis(20);
+
+        let guard = tracker.lock_guild("1").await;
+        // 同一公會的加入須等待前一個完成，其他公會不受影響
+        assert!(tokio::time::timeout(wait, tracker.lock_guild("1"))
+            .await
+            .is_err());
+        assert!(tokio::time::timeout(wait, tracker.lock_guild("2"))
+            .await
+            .is_ok());
+
+        drop(guard);
+        assert!(tokio::time::timeout(wait, tracker.lock_guild("1"))
+            .await
+            .is_ok());
+    }
+}
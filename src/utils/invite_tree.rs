@@ -0,0 +1,91 @@
+use crate::utils::db::InviteeRecord;
+use std::collections::HashMap;
+
+/// Renders an invite chain as indented lines of user mentions: `ancestors` from the
+/// root down to whoever invited `user`, then `user` in bold, then their invitees.
+pub fn render(ancestors: &[String], user: &str, invitees: &[InviteeRecord]) -> Vec<String> {
+    let mut lines: Vec<String> = ancestors
+        .iter()
+        .enumerate()
+        .map(|(depth, id)| line(depth, &format!("<@{}>", id)))
+        .collect();
+    lines.push(line(ancestors.len(), &format!("**<@{}>**", user)));
+
+    let mut children: HashMap<&str, Vec<&InviteeRecord>> = HashMap::new();
+    for invitee in invitees {
+        children
+            .entry(invitee.inviter_id.as_str())
+            .or_default()
+            .push(invitee);
+    }
+
+    // Depth-first from `user`, so every invitee sits right below their inviter
+    let mut stack: Vec<&InviteeRecord> = children
+        .get(user)
+        .map(|direct| direct.iter().rev().copied().collect())
+        .unwrap_or_default();
+    while let Some(invitee) = stack.pop() {
+        lines.push(line(
+            ancestors.len() + invitee.depth as usize,
+            &format!("<@{}>", invitee.user_id),
+        ));
+        if let Some(next) = children.get(invitee.user_id.as_str()) {
+            stack.extend(
+                next.iter()
+                    .rev()
+                    .filter(|child| child.depth == invitee.depth + 1),
+            );
+        }
+    }
+    lines
+}
+
+fn line(depth: usize, mention: &str) -> String {
+    if depth == 0 {
+        mention.to_string()
+    } else {
+        // Discord trims leading spaces, but not em spaces
+        format!("{}└ {}", "\u{2003}".repeat(depth - 1), mention)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invitee(user_id: &str, inviter_id: &str, depth: i64) -> InviteeRecord {
+        InviteeRecord {
+            user_id: user_id.to_string(),
+            inviter_id: inviter_id.to_string(),
+            depth,
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let lines = render(
+            &["root".to_string()],
+            "alice",
+            &[
+                invitee("bob", "alice", 1),
+                invitee("dave", "alice", 1),
+                invitee("carol", "bob", 2),
+            ],
+        );
+        assert_eq!(
+            lines,
+            vec![
+                "<@root>",
+                "└ **<@alice>**",
+                "\u{2003}└ <@bob>",
+                "\u{2003}\u{2003}└ <@carol>",
+                "\u{2003}└ <@dave>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_without_invites() {
+        assert_eq!(render(&[], "alice", &[]), vec!["**<@alice>**"]);
+    }
+}
@@ -1,5 +1,19 @@
+pub mod alt_detection;
+pub mod approval;
 pub mod config;
 pub mod db;
+pub mod export;
 pub mod i18n;
+pub mod import;
+pub mod invite_tracker;
+pub mod invite_tree;
+pub mod mod_log;
+pub mod overrides;
+pub mod pagination;
+pub mod permissions;
+pub mod reservation;
+pub mod revoke;
+pub mod rewards;
 #[cfg(test)]
 pub mod test_helpers;
+pub mod welcome;
@@ -0,0 +1,31 @@
+use crate::utils::config::AllowedGuild;
+use log::warn;
+use poise::serenity_prelude::{ChannelId, CreateEmbed, CreateMessage, Http};
+use std::num::NonZeroU64;
+
+/// Posts a report to the guild's `mod_log_channel`, if one is configured.
+pub async fn post(http: &Http, guild_config: &AllowedGuild, embed: CreateEmbed) {
+    let Some(channel) = guild_config.mod_log_channel.as_deref() else {
+        return;
+    };
+    let channel_id = match channel.parse::<NonZeroU64>() {
+        Ok(id) => ChannelId::from(id),
+        Err(_) => {
+            warn!(
+                "Invalid mod log channel {:?} of guild {}",
+                channel, guild_config.id
+            );
+            return;
+        }
+    };
+
+    if let Err(e) = channel_id
+        .send_message(http, CreateMessage::new().embed(embed))
+        .await
+    {
+        warn!(
+            "Failed to post to mod log of guild {}: {}",
+            guild_config.id, e
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use crate::utils::config::InviteLimit;
+use crate::utils::db::InviteOverride;
+
+/// A moderator's exception to the limit a member's roles give them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitOverride {
+    /// Invites on top of the role limit.
+    Extra(i32),
+    /// A limit that replaces the role limit, and applies even without an allowed role.
+    Limit(InviteLimit),
+    /// The member may not create invites at all.
+    Deny,
+}
+
+impl LimitOverride {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LimitOverride::Extra(_) => "extra",
+            LimitOverride::Limit(_) => "limit",
+            LimitOverride::Deny => "deny",
+        }
+    }
+
+    /// Reads a stored override, ignoring rows with an unknown kind or missing values.
+    pub fn from_record(record: &InviteOverride) -> Option<Self> {
+        match record.kind.as_str() {
+            "extra" => Some(LimitOverride::Extra(record.count? as i32)),
+            "limit" => Some(LimitOverride::Limit(InviteLimit {
+                count: record.count? as i32,
+                days: record.days? as i32,
+            })),
+            "deny" => Some(LimitOverride::Deny),
+            _ => None,
+        }
+    }
+
+    /// The limit after applying this override to `role_limit`, or `None` if the
+    /// member may not create invites.
+    pub fn apply(&self, role_limit: Option<InviteLimit>) -> Option<InviteLimit> {
+        match self {
+            LimitOverride::Extra(extra) => role_limit.map(|limit| InviteLimit {
+                count: limit.count + extra,
+                ..limit
+            }),
+            LimitOverride::Limit(limit) => Some(*limit),
+            LimitOverride::Deny => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_override() {
+        let role_limit = Some(InviteLimit { count: 5, days: 30 });
+        let custom = InviteLimit { count: 1, days: 7 };
+
+        assert_eq!(
+            LimitOverride::Extra(3).apply(role_limit),
+            Some(InviteLimit { count: 8, days: 30 })
+        );
+        assert_eq!(LimitOverride::Extra(3).apply(None), None);
+        assert_eq!(LimitOverride::Limit(custom).apply(role_limit), Some(custom));
+        assert_eq!(LimitOverride::Limit(custom).apply(None), Some(custom));
+        assert_eq!(LimitOverride::Deny.apply(role_limit), None);
+    }
+
+    #[test]
+    fn test_from_record() {
+        let record = |kind: &str, count, days| InviteOverride {
+            kind: kind.to_string(),
+            count,
+            days,
+        };
+
+        assert_eq!(
+            LimitOverride::from_record(&record("limit", Some(2), Some(14))),
+            Some(LimitOverride::Limit(InviteLimit { count: 2, days: 14 }))
+        );
+        assert_eq!(
+            LimitOverride::from_record(&record("deny", None, None)),
+            Some(LimitOverride::Deny)
+        );
+        assert_eq!(
+            LimitOverride::from_record(&record("limit", Some(2), None)),
+            None
+        );
+        assert_eq!(
+            LimitOverride::from_record(&record("other", None, None)),
+            None
+        );
+    }
+}
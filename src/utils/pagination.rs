@@ -0,0 +1,84 @@
+use crate::{Context, Error};
+use poise::serenity_prelude::{
+    self as serenity, CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+};
+use poise::CreateReply;
+use std::time::Duration;
+
+/// How long the buttons keep working after the last press.
+const TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Sends `pages` as an embed with previous/next buttons.
+///
+/// Based on `poise::builtins::paginate`, but pages are full embeds and the buttons are
+/// removed once they time out. Returns only after that, so call it last.
+pub async fn paginate(
+    ctx: Context<'_>,
+    pages: Vec<CreateEmbed>,
+    ephemeral: bool,
+) -> Result<(), Error> {
+    let Some(first) = pages.first() else {
+        return Ok(());
+    };
+    if pages.len() == 1 {
+        ctx.send(
+            CreateReply::default()
+                .embed(first.clone())
+                .ephemeral(ephemeral),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let ctx_id = ctx.id();
+    let prev_button_id = format!("{}prev", ctx_id);
+    let next_button_id = format!("{}next", ctx_id);
+    let buttons = CreateActionRow::Buttons(vec![
+        CreateButton::new(&prev_button_id).emoji('◀'),
+        CreateButton::new(&next_button_id).emoji('▶'),
+    ]);
+
+    let reply = ctx
+        .send(
+            CreateReply::default()
+                .embed(first.clone())
+                .components(vec![buttons])
+                .ephemeral(ephemeral),
+        )
+        .await?;
+
+    let mut current_page = 0;
+    while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+        .timeout(TIMEOUT)
+        .await
+    {
+        if press.data.custom_id == next_button_id {
+            current_page = (current_page + 1) % pages.len();
+        } else if press.data.custom_id == prev_button_id {
+            current_page = current_page.checked_sub(1).unwrap_or(pages.len() - 1);
+        } else {
+            continue;
+        }
+
+        press
+            .create_response(
+                ctx.serenity_context(),
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new().embed(pages[current_page].clone()),
+                ),
+            )
+            .await?;
+    }
+
+    reply
+        .edit(
+            ctx,
+            CreateReply::default()
+                .embed(pages[current_page].clone())
+                .components(vec![]),
+        )
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use crate::utils::config::AllowedGuild;
+use poise::serenity_prelude::{Member, Permissions};
+
+/// Whether `member` may manage every invite of the guild: server managers and
+/// holders of one of the guild's configured `admin_roles`.
+pub fn is_admin(member: &Member, guild_config: &AllowedGuild) -> bool {
+    let has_permission = member.permissions.is_some_and(|permissions| {
+        permissions.contains(Permissions::ADMINISTRATOR)
+            || permissions.contains(Permissions::MANAGE_GUILD)
+    });
+
+    has_permission
+        || member
+            .roles
+            .iter()
+            .any(|role_id| guild_config.admin_roles.contains(&role_id.to_string()))
+}
@@ -0,0 +1,82 @@
+use std::num::NonZeroU64;
+
+/// Turns what a member typed into the form stored in `intended_for`: a user ID,
+/// or a lowercase username. Accepts mentions and a leading `@`. `None` if the
+/// input can't be a Discord account.
+pub fn normalize(input: &str) -> Option<String> {
+    let input = input.trim();
+    let mention = input
+        .strip_prefix("<@")
+        .and_then(|rest| rest.strip_suffix('>'))
+        .map(|id| id.trim_start_matches('!'));
+    if let Some(id) = mention {
+        return id.parse::<NonZeroU64>().ok().map(|id| id.to_string());
+    }
+    if !input.is_empty() && input.chars().all(|c| c.is_ascii_digit()) {
+        return input.parse::<NonZeroU64>().ok().map(|id| id.to_string());
+    }
+
+    // Discord usernames: 2-32 characters of lowercase letters, digits, `_` and `.`
+    let username = input.strip_prefix('@').unwrap_or(input).to_lowercase();
+    let valid = (2..=32).contains(&username.chars().count())
+        && username
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.');
+    valid.then_some(username)
+}
+
+/// The reserved account's user ID, if the reservation was made by ID.
+pub fn user_id(intended_for: &str) -> Option<u64> {
+    intended_for.parse::<NonZeroU64>().ok().map(NonZeroU64::get)
+}
+
+/// Whether the joining account is the one the link was reserved for.
+pub fn matches(intended_for: &str, user_id: u64, username: &str) -> bool {
+    match self::user_id(intended_for) {
+        Some(id) => id == user_id,
+        None => intended_for.eq_ignore_ascii_case(username),
+    }
+}
+
+/// A mention for reservations by ID, `@username` otherwise.
+pub fn display(intended_for: &str) -> String {
+    match user_id(intended_for) {
+        Some(id) => format!("<@{}>", id),
+        None => format!("@{}", intended_for),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("123456789012345678").as_deref(),
+            Some("123456789012345678")
+        );
+        assert_eq!(
+            normalize("<@!123456789012345678>").as_deref(),
+            Some("123456789012345678")
+        );
+        assert_eq!(normalize(" @Some.User_1 ").as_deref(), Some("some.user_1"));
+        // 含空白或過短的名稱不是合法帳號
+        assert_eq!(normalize("some user"), None);
+        assert_eq!(normalize("a"), None);
+        assert_eq!(normalize("<@abc>"), None);
+        // 0 不是合法的使用者 ID
+        assert_eq!(normalize("0"), None);
+        assert_eq!(normalize("00"), None);
+        assert_eq!(normalize("<@0>"), None);
+        assert_eq!(user_id("0"), None);
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("123", 123, "someone"));
+        assert!(!matches("123", 456, "123"));
+        assert!(matches("some.user", 456, "Some.User"));
+        assert!(!matches("some.user", 456, "other"));
+    }
+}
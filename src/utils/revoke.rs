@@ -0,0 +1,93 @@
+use crate::utils::invite_tracker::InviteTracker;
+use log::warn;
+use poise::serenity_prelude::Http;
+use sqlx::SqlitePool;
+
+#[derive(Debug)]
+pub enum RevokeError {
+    NotFound,
+    Forbidden,
+    AlreadyUsed,
+    AlreadyRevoked,
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for RevokeError {
+    fn from(e: sqlx::Error) -> Self {
+        RevokeError::Database(e)
+    }
+}
+
+/// Who is asking to revoke an invite.
+pub struct Revoker<'a> {
+    pub user_id: &'a str,
+    pub is_admin: bool,
+    /// Restricts the lookup to one guild; `None` for the admin API.
+    pub guild_id: Option<&'a str>,
+}
+
+/// Accepts either a bare invite id or a full `/invite/{id}` URL.
+pub fn parse_invite_id(input: &str) -> &str {
+    input
+        .trim()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+}
+
+/// Revokes a bot invite and deletes its Discord invite, if one was created.
+pub async fn revoke_invite(
+    http: &Http,
+    db: &SqlitePool,
+    tracker: &InviteTracker,
+    invite_id: &str,
+    revoker: Revoker<'_>,
+) -> Result<(), RevokeError> {
+    let invite = crate::utils::db::get_invite(db, invite_id)
+        .await?
+        .filter(|invite| revoker.guild_id.is_none_or(|id| id == invite.guild_id))
+        .ok_or(RevokeError::NotFound)?;
+
+    if !revoker.is_admin && invite.creator_id != revoker.user_id {
+        return Err(RevokeError::Forbidden);
+    }
+    if invite.revoked_at.is_some() {
+        return Err(RevokeError::AlreadyRevoked);
+    }
+    if invite.used_at.is_some() && invite.max_uses <= 1 {
+        return Err(RevokeError::AlreadyUsed);
+    }
+
+    if !crate::utils::db::revoke_invite(db, invite_id, revoker.user_id).await? {
+        return Err(RevokeError::AlreadyRevoked);
+    }
+
+    if let Some(code) = invite.discord_invite_code {
+        tracker.remove(&invite.guild_id, &code).await;
+        if let Err(e) = http.delete_invite(&code, Some("Invite revoked")).await {
+            // The code may already be gone through expiry
+            warn!("Failed to delete revoked invite {}: {}", code, e);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_invite_id() {
+        assert_eq!(parse_invite_id("abc-123"), "abc-123");
+        assert_eq!(
+            parse_invite_id("https://example.com/invite/abc-123"),
+            "abc-123"
+        );
+        assert_eq!(
+            parse_invite_id(" https://example.com/invite/abc-123/ "),
+            "abc-123"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use crate::utils::config::{AllowedGuild, RewardTier};
+use crate::Data;
+use log::{debug, info, warn};
+use poise::serenity_prelude::{GuildId, Http, RoleId, UserId};
+use std::num::NonZeroU64;
+
+/// How far a member is towards one reward tier.
+pub struct TierProgress<'a> {
+    pub tier: &'a RewardTier,
+    pub count: i64,
+}
+
+impl TierProgress<'_> {
+    pub fn earned(&self) -> bool {
+        self.count >= i64::from(self.tier.count)
+    }
+
+    /// Invitees still needed for the tier.
+    pub fn remaining(&self) -> i64 {
+        (i64::from(self.tier.count) - self.count).max(0)
+    }
+}
+
+/// A member's progress on each of the guild's reward tiers, lowest threshold first.
+pub async fn progress<'a>(
+    data: &Data,
+    guild_config: &'a AllowedGuild,
+    user_id: &str,
+) -> Result<Vec<TierProgress<'a>>, sqlx::Error> {
+    let mut progress = Vec::new();
+    for tier in guild_config.reward_tiers() {
+        let count = crate::utils::db::count_reward_invitees(
+            &data.db,
+            &guild_config.id,
+            user_id,
+            tier.days,
+            guild_config.reward_retention(tier),
+        )
+        .await?;
+        progress.push(TierProgress { tier, count });
+    }
+    Ok(progress)
+}
+
+/// Gives an inviter the reward roles they've earned and takes back the ones
+/// they no longer qualify for.
+pub async fn evaluate(http: &Http, data: &Data, guild_config: &AllowedGuild, user_id: &str) {
+    if guild_config.rewards.is_empty() {
+        return;
+    }
+    let (guild_id, user_id) = match (
+        guild_config.id.parse::<NonZeroU64>(),
+        user_id.parse::<NonZeroU64>(),
+    ) {
+        (Ok(guild_id), Ok(user_id)) => (GuildId::from(guild_id), UserId::from(user_id)),
+        _ => return,
+    };
+    let progress = match progress(data, guild_config, &user_id.to_string()).await {
+        Ok(progress) => progress,
+        Err(e) => {
+            warn!("Failed to count rewards of {}: {}", user_id, e);
+            return;
+        }
+    };
+    let member = match guild_id.member(http, user_id).await {
+        Ok(member) => member,
+        Err(e) => {
+            debug!(
+                "Inviter {} of guild {} not found for rewards: {}",
+                user_id, guild_id, e
+            );
+            return;
+        }
+    };
+
+    for tier in progress {
+        let Ok(role_id) = tier.tier.role.parse::<NonZeroU64>().map(RoleId::from) else {
+            continue;
+        };
+        let held = member.roles.contains(&role_id);
+        let result = if tier.earned() && !held {
+            member.add_role(http, role_id).await
+        } else if !tier.earned() && held {
+            member.remove_role(http, role_id).await
+        } else {
+            continue;
+        };
+
+        match result {
+            Ok(()) => info!(
+                "{} reward role {} for {} of guild {} at {} invitees.",
+                if held { "Took" } else { "Gave" },
+                role_id,
+                user_id,
+                guild_id,
+                tier.count
+            ),
+            Err(e) => warn!(
+                "Failed to update reward role {} of {}: {}",
+                role_id, user_id, e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tier_progress() {
+        let tier = RewardTier {
+            role: "1".to_string(),
+            count: 5,
+            ..Default::default()
+        };
+        let progress = |count| TierProgress { tier: &tier, count };
+
+        assert!(!progress(3).earned());
+        assert_eq!(progress(3).remaining(), 2);
+        // 達到門檻即取得，超過時不會是負數
+        assert!(progress(5).earned());
+        assert_eq!(progress(7).remaining(), 0);
+    }
+}
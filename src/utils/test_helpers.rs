@@ -21,6 +21,7 @@ impl TestContext {
             server: crate::utils::config::ServerConfig {
                 external_url: "http://localhost:8080".to_string(),
                 bind: "127.0.0.1:8080".to_string(),
+                admin_token: Some("test_admin_token".to_string()),
             },
             i18n: crate::utils::config::I18nConfig {
                 default_locale: "en".to_string(),
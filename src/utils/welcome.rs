@@ -0,0 +1,165 @@
+use crate::t;
+use crate::utils::config::{AllowedGuild, Welcome};
+use crate::Data;
+use log::warn;
+use poise::serenity_prelude::{
+    self as serenity, ChannelId, CreateAllowedMentions, CreateEmbed, CreateEmbedFooter,
+    CreateMessage, UserId,
+};
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+
+/// Posts the guild's welcome message for a member who joined through a bot
+/// invite, and DMs their inviter if the guild asks for it.
+pub async fn greet(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_config: &AllowedGuild,
+    welcome: &Welcome,
+    inviter_id: &str,
+    member: &serenity::Member,
+) {
+    let config = data.config.get();
+    let locale = config.get_guild_locale(&guild_config.id);
+    let count = crate::utils::db::count_invitees(&data.db, &guild_config.id, inviter_id)
+        .await
+        .unwrap_or_default();
+
+    let mut params = HashMap::new();
+    params.insert("user", format!("<@{}>", member.user.id));
+    params.insert("inviter", format!("<@{}>", inviter_id));
+    params.insert(
+        "guild",
+        ctx.cache
+            .guild(member.guild_id)
+            .map(|guild| guild.name.clone())
+            .unwrap_or_else(|| guild_config.name.clone()),
+    );
+    params.insert("count", count.to_string());
+    params.insert("ordinal", ordinal(count));
+
+    if let Some(channel_id) = welcome
+        .channel
+        .as_deref()
+        .and_then(|id| id.parse::<NonZeroU64>().ok())
+    {
+        let message = render(
+            locale,
+            welcome.message.as_deref(),
+            "welcome.message",
+            &params,
+        );
+        // Only the newcomer is pinged; the inviter hears about it by DM if at all
+        if let Err(e) = ChannelId::from(channel_id)
+            .send_message(
+                &ctx.http,
+                CreateMessage::new()
+                    .content(message)
+                    .allowed_mentions(CreateAllowedMentions::new().users([member.user.id])),
+            )
+            .await
+        {
+            warn!(
+                "Failed to welcome member {} of guild {}: {}",
+                member.user.id, guild_config.id, e
+            );
+        }
+    }
+
+    if !welcome.dm_inviter {
+        return;
+    }
+    let Ok(inviter_id) = inviter_id.parse::<NonZeroU64>() else {
+        return;
+    };
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "welcome.dm.title"))
+        .description(render(
+            locale,
+            welcome.dm_message.as_deref(),
+            "welcome.dm.description",
+            &params,
+        ))
+        .color(0x4CACEE)
+        .thumbnail(member.user.face())
+        .footer(CreateEmbedFooter::new(t!(
+            locale,
+            "welcome.dm.footer",
+            params.clone()
+        )));
+    if let Err(e) = UserId::from(inviter_id)
+        .direct_message(ctx, CreateMessage::new().embed(embed))
+        .await
+    {
+        warn!(
+            "Failed to DM {} about invitee {}: {}",
+            inviter_id, member.user.id, e
+        );
+    }
+}
+
+/// The guild's own template if it set one, else the locale's.
+fn render(
+    locale: &str,
+    template: Option<&str>,
+    key: &str,
+    params: &HashMap<&str, String>,
+) -> String {
+    match template {
+        Some(template) => crate::utils::i18n::fill(template, params),
+        None => t!(locale, key, params.clone()),
+    }
+}
+
+/// `1st`, `2nd`, `3rd`, `4th`, … `11th`, `12th`, `13th`, `21st`.
+fn ordinal(n: i64) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordinal() {
+        let ordinals: Vec<String> = [1, 2, 3, 4, 11, 12, 13, 21, 102, 111]
+            .into_iter()
+            .map(ordinal)
+            .collect();
+        assert_eq!(
+            ordinals,
+            ["1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "102nd", "111th"]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let params = HashMap::from([
+            ("user", "<@1>".to_string()),
+            ("inviter", "<@2>".to_string()),
+            ("ordinal", "5th".to_string()),
+        ]);
+
+        // 伺服器自訂的模板優先於語系檔
+        assert_eq!(
+            render(
+                "en",
+                Some("Hi {user}, thanks {inviter}"),
+                "welcome.message",
+                &params
+            ),
+            "Hi <@1>, thanks <@2>"
+        );
+        assert_eq!(
+            render("en", None, "welcome.message", &params),
+            "Welcome <@1>, invited by <@2> (their 5th invite!)"
+        );
+    }
+}